@@ -24,11 +24,15 @@ fn main() -> io::Result<()> {
             "Assign   : Token name, Box<Expr> value",
             "Binary   : Box<Expr> left, Token operator, Box<Expr> right",
             "Call     : Rc<Expr> callee, Token paren, Vec<Expr> arguments",
+            "Get      : Box<Expr> object, Token name",
             "Grouping : Box<Expr> expression",
             "Literal  : Option<Literal> value",
             "Unary    : Token operator, Box<Expr> right",
             "Variable : Token name",
             "Logical  : Box<Expr> left, Token operator, Box<Expr> right",
+            "List     : Token bracket, Vec<Expr> elements",
+            "Spread   : Token ellipsis, Box<Expr> expression",
+            "Unpack   : Token bracket, Vec<Token> names, Box<Expr> value",
         ],
         &[
             "crate::error::*",
@@ -43,13 +47,15 @@ fn main() -> io::Result<()> {
         &[
             "Block  : Vec<Stmt> statements",
             "Expression : Expr expression",
-            "Function : Token name, Rc<Vec<Token>> params, Rc<Vec<Stmt>> body",
+            "Function : Token name, Rc<Vec<Token>> params, Rc<Vec<Option<Token>>> param_types, Option<Token> return_type, Rc<Vec<Stmt>> body",
             "If       : Expr condition, Box<Stmt> then_branch, Option<Box<Stmt>>  else_branch",
             "Print : Expr expression",
             "Return  : Token keyword, Option<Expr> value",
-            "Var : Token name, Option<Expr> initializer",
-            "While : Expr condition, Box<Stmt> body",
+            "Var : Token name, Option<Token> type_annotation, Option<Expr> initializer",
+            "Destructure : Token bracket, Vec<Token> names, Expr initializer",
+            "While : Token keyword, Expr condition, Box<Stmt> body",
             "Break : Token token",
+            "Assert : Token keyword, Expr condition, Option<Expr> message, String source",
         ],
         &[
             "crate::error::*",
@@ -79,8 +85,6 @@ fn define_ast(
 
     prepare_trait(&file, &tree_types, base_name)?;
 
-    prepare_visitors(&file, &tree_types, base_name)?;
-
     Ok(())
 }
 
@@ -177,27 +181,3 @@ fn prepare_trait(
     writeln!(file, "}}\n")?;
     Ok(())
 }
-
-fn prepare_visitors(
-    mut file: &std::fs::File,
-    tree_types: &Vec<TreeType>,
-    base_name: &str,
-) -> Result<(), io::Error> {
-    for t in tree_types {
-        writeln!(file, "impl {} {{", t.class_name)?;
-        writeln!(
-            file,
-            "    pub fn accept<T>(&self, visitor: &dyn {}Visitor<T>) -> Result<T, LoxResult> {{",
-            base_name
-        )?;
-        writeln!(
-            file,
-            "        visitor.visit_{}_{}(self)",
-            t.base_class.to_lowercase(),
-            base_name.to_lowercase()
-        )?;
-        writeln!(file, "    }}")?;
-        writeln!(file, "}}")?;
-    }
-    Ok(())
-}
@@ -32,7 +32,17 @@ pub trait LoxCallable {
         -> Result<Literal, LoxResult>;
     fn arity(&self) -> usize;
 
+    /// Largest number of arguments accepted, `None` when variadic.
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
     fn to_string(&self) -> String;
+
+    /// Name shown in call stacks.
+    fn name(&self) -> String {
+        self.to_string()
+    }
 }
 
 impl LoxCallable for Callable {
@@ -48,7 +58,30 @@ impl LoxCallable for Callable {
         self.func.arity()
     }
 
+    fn max_arity(&self) -> Option<usize> {
+        self.func.max_arity()
+    }
+
     fn to_string(&self) -> String {
         self.func.to_string()
     }
+
+    fn name(&self) -> String {
+        self.func.name()
+    }
+}
+
+/// Describes the accepted number of arguments ("2", "1 to 2", "at least 1")
+/// when `received` is not one of them.
+pub fn arity_mismatch(callable: &dyn LoxCallable, received: usize) -> Option<String> {
+    let arity = callable.arity();
+    let max_arity = callable.max_arity();
+    if received >= arity && max_arity.is_none_or(|max| received <= max) {
+        return None;
+    }
+    Some(match max_arity {
+        Some(max) if max == arity => format!("{arity}"),
+        Some(max) => format!("{arity} to {max}"),
+        None => format!("at least {arity}"),
+    })
 }
@@ -0,0 +1,141 @@
+use crate::callable::LoxCallable;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeFunction, ParamType};
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(
+        NativeFunction::new("type", |_, args| {
+            Ok(Literal::String(type_name(&args.get(0)).to_string()))
+        })
+        .param("value", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("str", |_, args| {
+            Ok(Literal::String(args.get(0).to_string()))
+        })
+        .param("value", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("num", |_, args| match args.get(0) {
+            Literal::Number(x) => Ok(Literal::Number(x)),
+            Literal::String(text) => text
+                .trim()
+                .parse::<f64>()
+                .map(Literal::Number)
+                .map_err(|_| args.error(&format!("'{text}' is not a number"))),
+            other => Err(args.error(&format!(
+                "cannot convert {} {other} to a number",
+                type_name(&other)
+            ))),
+        })
+        .param("value", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("bool", |interpreter, args| {
+            Ok(Literal::Boolean(interpreter.is_truthy(&args.get(0))))
+        })
+        .param("value", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("arity", |_, args| {
+            Ok(Literal::Number(args.function(0)?.arity() as f64))
+        })
+        .param("function", ParamType::Function),
+    );
+}
+
+/// Name of the value's runtime type, as returned by `type()`.
+pub fn type_name(value: &Literal) -> &'static str {
+    match value {
+        Literal::Number(_) => "number",
+        Literal::String(_) => "string",
+        Literal::Boolean(_) => "bool",
+        Literal::Nil => "nil",
+        Literal::Func(_) => "function",
+        Literal::List(_) => "list",
+        Literal::Namespace(_) => "namespace",
+        Literal::UserData(_) => "userdata",
+        Literal::Identifier(_) => "identifier",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_type_names() {
+        let clock = Interpreter::new()
+            .globals
+            .borrow()
+            .get_value("clock")
+            .unwrap();
+        assert_eq!(
+            call_native(None, "type", [Literal::Number(1.0)]).unwrap(),
+            Literal::from("number")
+        );
+        assert_eq!(
+            call_native(None, "type", [Literal::from("x")]).unwrap(),
+            Literal::from("string")
+        );
+        assert_eq!(
+            call_native(None, "type", [Literal::Boolean(true)]).unwrap(),
+            Literal::from("bool")
+        );
+        assert_eq!(
+            call_native(None, "type", [Literal::Nil]).unwrap(),
+            Literal::from("nil")
+        );
+        assert_eq!(
+            call_native(None, "type", [clock]).unwrap(),
+            Literal::from("function")
+        );
+        assert_eq!(
+            call_native(None, "type", [Literal::List(Rc::new(vec![]))]).unwrap(),
+            Literal::from("list")
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            call_native(None, "str", [Literal::Number(2.5)]).unwrap(),
+            Literal::from("2.5")
+        );
+        assert_eq!(
+            call_native(None, "num", [Literal::from(" 42 ")]).unwrap(),
+            Literal::Number(42.0)
+        );
+        assert!(call_native(None, "num", [Literal::from("4x2")]).is_err());
+        assert!(call_native(None, "num", [Literal::Nil]).is_err());
+        assert_eq!(
+            call_native(None, "bool", [Literal::Number(0.0)]).unwrap(),
+            Literal::Boolean(true)
+        );
+        assert_eq!(
+            call_native(None, "bool", [Literal::Nil]).unwrap(),
+            Literal::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_arity() {
+        let len = Interpreter::new()
+            .globals
+            .borrow()
+            .get_value("len")
+            .unwrap();
+        assert_eq!(
+            call_native(None, "arity", [len]).unwrap(),
+            Literal::Number(1.0)
+        );
+        assert!(call_native(None, "arity", [Literal::Nil]).is_err());
+    }
+}
@@ -48,6 +48,10 @@ impl Environment {
         }
     }
 
+    pub fn get_value(&self, name: &str) -> Option<Literal> {
+        self.values.get(name).cloned()
+    }
+
     pub fn assign(&mut self, name: &Token, value: Literal) -> Result<(), LoxResult> {
         if let Entry::Occupied(mut entry) = self.values.entry(name.as_string().to_string()) {
             entry.insert(value);
@@ -137,7 +141,7 @@ mod tests {
 
         env.define(key_val.0, key_val.1);
 
-        assert!(env.values.contains_key(&"my_bool".to_string()));
+        assert!(env.values.contains_key("my_bool"));
 
         let token = Token::new(TokenType::Identifier, "my_bool".to_string(), 0, None);
 
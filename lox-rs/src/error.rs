@@ -1,14 +1,60 @@
 use std::fmt;
+use std::io::{self, Write};
 
 use crate::{literal::Literal, token::Token, token_type::TokenType};
+
+/// Frames shown at each end of a long call stack; the middle is elided.
+const TRACE_EDGE: usize = 10;
+
+/// A call in progress: the function and the token it was called at.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub function: String,
+    pub site: Token,
+}
+
+impl Frame {
+    /// Whether the host made the call (see [`Interpreter::call_global`]),
+    /// in which case `site` is a synthetic token on line 0.
+    ///
+    /// [`Interpreter::call_global`]: crate::interpreter::Interpreter::call_global
+    pub fn is_host_call(&self) -> bool {
+        self.site.line == 0
+    }
+}
+
 #[derive(Debug)]
 pub enum LoxResult {
-    ParseError { token: Token, message: String },
-    RuntimeError { token: Token, message: String },
-    ScannerError { line: usize, message: String },
-    SystemError { message: String },
-    Return { value: Literal },
+    ParseError {
+        token: Token,
+        message: String,
+    },
+    /// `trace` holds the calls the error escaped from, innermost first.
+    RuntimeError {
+        token: Token,
+        message: String,
+        trace: Vec<Frame>,
+    },
+    TypeError {
+        token: Token,
+        message: String,
+    },
+    ScannerError {
+        line: usize,
+        message: String,
+    },
+    SystemError {
+        message: String,
+    },
+    Return {
+        value: Literal,
+    },
     Break,
+    /// Raised by `exit()`. It unwinds the whole evaluation and leaves ending
+    /// the process to the host.
+    Exit {
+        code: i32,
+    },
 }
 
 impl fmt::Display for LoxResult {
@@ -17,70 +63,122 @@ impl fmt::Display for LoxResult {
             Self::ParseError { token, message } => {
                 write!(f, "Line: {}, Error: {}", token.line, message)
             }
-            Self::RuntimeError { token, message } => {
+            Self::RuntimeError { token, message, .. } => {
                 write!(f, "Line: {}, Error: {}", token.line, message)
             }
+            Self::TypeError { token, message } => {
+                write!(f, "Line: {}, Type error: {}", token.line, message)
+            }
 
             Self::ScannerError { line, message } => write!(f, "Line: {}, Error: {}", line, message),
             Self::SystemError { message } => write!(f, "System error: {}", message),
             Self::Break => write!(f, ""),
             Self::Return { value: _ } => write!(f, ""),
+            Self::Exit { code } => write!(f, "Exit with status {}", code),
         }
     }
 }
 impl LoxResult {
     pub fn error(line: usize, message: &str) -> LoxResult {
-        let err = LoxResult::ScannerError {
+        LoxResult::ScannerError {
             line,
             message: message.to_string(),
-        };
-        err.report();
-        err
+        }
     }
     pub fn interp_error(token: &Token, message: &str) -> LoxResult {
-        let err = LoxResult::RuntimeError {
+        LoxResult::RuntimeError {
             token: token.clone(),
             message: message.to_string(),
-        };
-        err.report();
-        err
+            trace: Vec::new(),
+        }
     }
     pub fn parse_error(token: &Token, message: &str) -> LoxResult {
-        let err = LoxResult::ParseError {
+        LoxResult::ParseError {
             token: token.clone(),
             message: message.to_string(),
-        };
-        err.report();
-        err
+        }
+    }
+
+    pub fn type_error(token: &Token, message: &str) -> LoxResult {
+        LoxResult::TypeError {
+            token: token.clone(),
+            message: message.to_string(),
+        }
     }
 
     pub fn system_error(message: &str) -> LoxResult {
-        let err = LoxResult::SystemError {
+        LoxResult::SystemError {
             message: message.to_string(),
-        };
-        err.report();
-        err
+        }
     }
 
-    pub fn report(&self) {
+    /// Writes the error in the format used for diagnostics, preceded by the
+    /// call stack of runtime errors raised inside functions.
+    pub fn report(&self, out: &mut dyn Write) -> io::Result<()> {
+        if let Self::RuntimeError { trace, .. } = self {
+            Self::report_trace(trace, out)?;
+        }
         match self {
-            Self::ParseError { token, message } | Self::RuntimeError { token, message } => {
+            Self::ParseError { token, message } | Self::RuntimeError { token, message, .. } => {
                 if token.is(TokenType::Eof) {
-                    eprintln!("Line: {} at end: {}", token.line, message)
+                    writeln!(out, "Line: {} at end: {}", token.line, message)
                 } else {
-                    eprintln!("Line: {} at '{}' : {} ", token.line, token.lexeme, message);
+                    writeln!(
+                        out,
+                        "Line: {} at '{}' : {} ",
+                        token.line, token.lexeme, message
+                    )
                 }
             }
+            Self::TypeError { token, message } => {
+                writeln!(
+                    out,
+                    "Line: {} at '{}' : Type error: {} ",
+                    token.line, token.lexeme, message
+                )
+            }
             Self::ScannerError { line, message } => {
-                eprintln!("Line: {}: Error: {}", line, message);
+                writeln!(out, "Line: {}: Error: {}", line, message)
             }
             Self::SystemError { message } => {
-                eprintln!("System Error: {}", message);
+                writeln!(out, "System Error: {}", message)
             }
-            Self::Break => {}
-            Self::Return { value: _ } => {}
+            Self::Break => Ok(()),
+            Self::Return { value: _ } => Ok(()),
+            Self::Exit { .. } => Ok(()),
         }
     }
+    fn report_trace(trace: &[Frame], out: &mut dyn Write) -> io::Result<()> {
+        if trace.is_empty() {
+            return Ok(());
+        }
+        writeln!(out, "Call stack (innermost last):")?;
+        let write_frame = |out: &mut dyn Write, frame: &Frame| {
+            if frame.is_host_call() {
+                writeln!(out, "  host: {}()", frame.function)
+            } else {
+                writeln!(out, "  line {}: {}()", frame.site.line, frame.function)
+            }
+        };
+        if trace.len() <= 2 * TRACE_EDGE {
+            return trace
+                .iter()
+                .rev()
+                .try_for_each(|frame| write_frame(out, frame));
+        }
+        let (inner, rest) = trace.split_at(TRACE_EDGE);
+        let (omitted, outer) = rest.split_at(rest.len() - TRACE_EDGE);
+        outer
+            .iter()
+            .rev()
+            .try_for_each(|frame| write_frame(out, frame))?;
+        writeln!(out, "  ... {} more calls", omitted.len())?;
+        inner
+            .iter()
+            .rev()
+            .try_for_each(|frame| write_frame(out, frame))
+    }
+
     pub fn return_value(value: Literal) -> LoxResult {
         LoxResult::Return { value }
     }
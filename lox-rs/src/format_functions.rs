@@ -0,0 +1,261 @@
+//! `format`, `printf` and `write`. Templates use `{}` placeholders, filled
+//! from the arguments in order, or `{n}` to pick argument `n` (from 0). A
+//! placeholder may carry a spec after a colon, `{:[[fill]align][0][width][.precision]}`,
+//! where align is `<`, `>` or `^`: `{:>8.2}` right-aligns a number with two
+//! decimals in eight columns. `{{` and `}}` are literal braces, and since Lox
+//! strings have no escape sequences, templates understand `\n`, `\t` and `\\`.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+
+/// Largest width and precision a spec may ask for.
+const MAX_WIDTH: usize = 65535;
+const MAX_PRECISION: usize = 4096;
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(
+        NativeFunction::new("format", |_, args| {
+            let text = format(&args.string(0)?, args.rest(1)).map_err(|e| args.error(&e))?;
+            Ok(Literal::String(text))
+        })
+        .param("template", ParamType::String)
+        .variadic("values", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("printf", |interpreter, args| {
+            let text = format(&args.string(0)?, args.rest(1)).map_err(|e| args.error(&e))?;
+            emit(interpreter, args, &text)
+        })
+        .param("template", ParamType::String)
+        .variadic("values", ParamType::Any),
+    );
+
+    // `print` without the trailing newline.
+    interpreter.register_native(
+        NativeFunction::new("write", |interpreter, args| {
+            emit(interpreter, args, &args.get(0).to_string())
+        })
+        .param("value", ParamType::Any),
+    );
+}
+
+fn emit(interpreter: &Interpreter, args: &NativeArgs, text: &str) -> Result<Literal, LoxResult> {
+    let mut output = interpreter.output();
+    output
+        .write_all(text.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(|e| LoxResult::system_error(&format!("{}(): {}", args.name(), e)))?;
+    Ok(Literal::Nil)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+struct Spec {
+    fill: char,
+    align: Option<Align>,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+fn format(template: &str, values: &[Literal]) -> Result<String, String> {
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+    let mut next = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                output.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                output.push('}');
+            }
+            '\\' => match chars.peek() {
+                Some('n') => {
+                    chars.next();
+                    output.push('\n');
+                }
+                Some('t') => {
+                    chars.next();
+                    output.push('\t');
+                }
+                Some('\\') => {
+                    chars.next();
+                    output.push('\\');
+                }
+                _ => output.push('\\'),
+            },
+            '}' => return Err("unmatched '}' in template, use '}}' for a literal brace".into()),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(format!("unterminated placeholder '{{{placeholder}'")),
+                    }
+                }
+                let (position, spec) = placeholder
+                    .split_once(':')
+                    .unwrap_or((placeholder.as_str(), ""));
+                let index = if position.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    position
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid placeholder '{{{placeholder}}}'"))?
+                };
+                let value = values.get(index).ok_or_else(|| {
+                    format!(
+                        "placeholder '{{{placeholder}}}' refers to argument {index}, but only {} given",
+                        values.len()
+                    )
+                })?;
+                let spec = parse_spec(spec)
+                    .ok_or_else(|| format!("invalid format spec in '{{{placeholder}}}'"))?;
+                if spec.width > MAX_WIDTH || spec.precision.is_some_and(|p| p > MAX_PRECISION) {
+                    return Err(format!(
+                        "format spec in '{{{placeholder}}}' exceeds the maximum width of \
+                         {MAX_WIDTH} or precision of {MAX_PRECISION}"
+                    ));
+                }
+                output.push_str(&render(value, &spec));
+            }
+            c => output.push(c),
+        }
+    }
+    Ok(output)
+}
+
+fn parse_spec(spec: &str) -> Option<Spec> {
+    let chars: Vec<char> = spec.chars().collect();
+    let align_of = |c: char| match c {
+        '<' => Some(Align::Left),
+        '>' => Some(Align::Right),
+        '^' => Some(Align::Center),
+        _ => None,
+    };
+    let mut result = Spec {
+        fill: ' ',
+        align: None,
+        zero: false,
+        width: 0,
+        precision: None,
+    };
+    let mut i = 0;
+    if let Some(align) = chars.get(1).copied().and_then(align_of) {
+        result.fill = chars[0];
+        result.align = Some(align);
+        i = 2;
+    } else if let Some(align) = chars.first().copied().and_then(align_of) {
+        result.align = Some(align);
+        i = 1;
+    }
+    if chars.get(i) == Some(&'0') {
+        result.zero = true;
+        i += 1;
+    }
+    // Numbers too large for a usize saturate, so that they fail the bounds
+    // check instead of reading as a missing width.
+    let digits = |i: &mut usize| -> Option<usize> {
+        let start = *i;
+        while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+            *i += 1;
+        }
+        (*i > start).then(|| {
+            chars[start..*i]
+                .iter()
+                .collect::<String>()
+                .parse()
+                .unwrap_or(usize::MAX)
+        })
+    };
+    result.width = digits(&mut i).unwrap_or(0);
+    if chars.get(i) == Some(&'.') {
+        i += 1;
+        result.precision = Some(digits(&mut i)?);
+    }
+    (i == chars.len()).then_some(result)
+}
+
+fn render(value: &Literal, spec: &Spec) -> String {
+    let text = match (value, spec.precision) {
+        (Literal::Number(x), Some(precision)) => format!("{x:.precision$}"),
+        (_, Some(precision)) => value.to_string().chars().take(precision).collect(),
+        _ => value.to_string(),
+    };
+    let length = text.chars().count();
+    if length >= spec.width {
+        return text;
+    }
+    let padding = spec.width - length;
+    let is_number = matches!(value, Literal::Number(_));
+    if spec.zero && spec.align.is_none() && is_number {
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        return format!("{sign}{}{digits}", "0".repeat(padding));
+    }
+    let default = if is_number { Align::Right } else { Align::Left };
+    let fill = |count: usize| spec.fill.to_string().repeat(count);
+    match spec.align.unwrap_or(default) {
+        Align::Left => format!("{text}{}", fill(padding)),
+        Align::Right => format!("{}{text}", fill(padding)),
+        Align::Center => format!("{}{text}{}", fill(padding / 2), fill(padding - padding / 2)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn values() -> Vec<Literal> {
+        vec![
+            Literal::String("lox".to_string()),
+            Literal::Number(1.23456),
+            Literal::Number(-42.0),
+        ]
+    }
+
+    #[rstest]
+    #[case::sequential("{} {}", "lox 1.23456")]
+    #[case::positional("{2} {0} {0}", "-42 lox lox")]
+    #[case::escaped_braces("{{{}}}", "{lox}")]
+    #[case::precision("{1:.2}", "1.23")]
+    #[case::string_default_left("[{0:6}]", "[lox   ]")]
+    #[case::number_default_right("[{1:8.3}]", "[   1.235]")]
+    #[case::explicit_alignment("[{0:>5}|{0:^7}|{0:<4}]", "[  lox|  lox  |lox ]")]
+    #[case::fill("{0:*^9}", "***lox***")]
+    #[case::zero_padding("{2:06}", "-00042")]
+    #[case::truncated_string("{0:.2}", "lo")]
+    #[case::escapes(r"{}\n\t\\\x", "lox\n\t\\\\x")]
+    fn test_format(#[case] template: &str, #[case] expected: &str) {
+        assert_eq!(format(template, &values()).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::missing_argument("{3}")]
+    #[case::unterminated("{0")]
+    #[case::unmatched_close("}")]
+    #[case::bad_spec("{0:x}")]
+    #[case::bad_position("{a}")]
+    #[case::huge_precision("{1:.70000}")]
+    #[case::huge_width("{1:99999999999999999}")]
+    #[case::overflowing_width("{1:999999999999999999999999}")]
+    fn test_format_errors(#[case] template: &str) {
+        assert!(format(template, &values()).is_err());
+    }
+}
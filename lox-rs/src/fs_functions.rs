@@ -0,0 +1,264 @@
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Which paths the file-system natives may touch. Scoped roots are stored
+/// canonicalized so that `..` and symlinks cannot escape them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FsAccess {
+    #[default]
+    Denied,
+    Unrestricted,
+    Scoped(Vec<PathBuf>),
+}
+
+impl FsAccess {
+    pub fn scoped(root: &str) -> Result<FsAccess, LoxResult> {
+        match fs::canonicalize(root) {
+            Ok(root) => Ok(FsAccess::Scoped(vec![root])),
+            Err(e) => Err(LoxResult::system_error(&format!(
+                "Cannot use '{}' as file system root: {}",
+                root, e
+            ))),
+        }
+    }
+
+    /// Adds the roots of `other` to this scope; unrestricted access wins.
+    pub fn merge(self, other: FsAccess) -> FsAccess {
+        match (self, other) {
+            (FsAccess::Denied, access) | (access, FsAccess::Denied) => access,
+            (FsAccess::Scoped(mut roots), FsAccess::Scoped(more)) => {
+                roots.extend(more);
+                FsAccess::Scoped(roots)
+            }
+            _ => FsAccess::Unrestricted,
+        }
+    }
+
+    fn permits(&self, path: &Path) -> bool {
+        match self {
+            FsAccess::Denied => false,
+            FsAccess::Unrestricted => true,
+            FsAccess::Scoped(roots) => {
+                let Some(resolved) = FsAccess::resolve(path) else {
+                    return false;
+                };
+                roots.iter().any(|root| resolved.starts_with(root))
+            }
+        }
+    }
+
+    // Paths which do not exist yet (e.g. the target of `writeFile`) are
+    // resolved through their parent directory. A dangling symlink cannot be
+    // canonicalized either, but writing through it would create its target
+    // wherever it points, so it is not resolved at all.
+    fn resolve(path: &Path) -> Option<PathBuf> {
+        if let Ok(resolved) = fs::canonicalize(path) {
+            return Some(resolved);
+        }
+        if fs::symlink_metadata(path).is_ok() {
+            return None;
+        }
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        Some(fs::canonicalize(parent).ok()?.join(path.file_name()?))
+    }
+}
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(
+        NativeFunction::new("readFile", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            fs::read_to_string(&path)
+                .map(Literal::String)
+                .map_err(|e| failure(args, &path, e))
+        })
+        .param("path", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("readLines", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            let contents = fs::read_to_string(&path).map_err(|e| failure(args, &path, e))?;
+            let lines = contents
+                .lines()
+                .map(|line| Literal::String(line.to_string()))
+                .collect();
+            Ok(Literal::List(Rc::new(lines)))
+        })
+        .param("path", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("writeFile", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            fs::write(&path, args.string(1)?).map_err(|e| failure(args, &path, e))?;
+            Ok(Literal::Nil)
+        })
+        .param("path", ParamType::String)
+        .param("contents", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("appendFile", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            let contents = args.string(1)?;
+            fs::OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(contents.as_bytes()))
+                .map_err(|e| failure(args, &path, e))?;
+            Ok(Literal::Nil)
+        })
+        .param("path", ParamType::String)
+        .param("contents", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("exists", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            Ok(Literal::Boolean(Path::new(&path).exists()))
+        })
+        .param("path", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("listDir", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            let mut names = Vec::new();
+            for entry in fs::read_dir(&path).map_err(|e| failure(args, &path, e))? {
+                let entry = entry.map_err(|e| failure(args, &path, e))?;
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+            names.sort();
+            Ok(Literal::List(Rc::new(
+                names.into_iter().map(Literal::String).collect(),
+            )))
+        })
+        .param("path", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("removeFile", |interpreter, args| {
+            let path = permitted(interpreter, args)?;
+            fs::remove_file(&path).map_err(|e| failure(args, &path, e))?;
+            Ok(Literal::Nil)
+        })
+        .param("path", ParamType::String),
+    );
+}
+
+fn permitted(interpreter: &Interpreter, args: &NativeArgs) -> Result<String, LoxResult> {
+    let path = args.string(0)?;
+    match interpreter.fs_access() {
+        FsAccess::Denied => {
+            Err(args.error("file system access is disabled, start the interpreter with --allow-fs"))
+        }
+        access if !access.permits(Path::new(&path)) => Err(args.error(&format!(
+            "'{path}' is outside of the directories allowed by --allow-fs"
+        ))),
+        _ => Ok(path),
+    }
+}
+
+fn failure(args: &NativeArgs, path: &str, error: std::io::Error) -> LoxResult {
+    LoxResult::system_error(&format!("{}('{}'): {}", args.name(), path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native_in;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lox-fs-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_access_is_denied_by_default() {
+        let interpreter = Interpreter::new();
+        assert!(matches!(
+            call_native_in(&interpreter, None, "exists", ["."]),
+            Err(LoxResult::RuntimeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_write_append_and_read_back() {
+        let dir = scratch_dir("roundtrip");
+        let file = dir.join("data.txt");
+        let file = file.to_str().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_fs_access(FsAccess::Unrestricted);
+
+        call_native_in(&interpreter, None, "writeFile", [file, "one\n"]).unwrap();
+        call_native_in(&interpreter, None, "appendFile", [file, "two\n"]).unwrap();
+        assert_eq!(
+            call_native_in(&interpreter, None, "readLines", [file])
+                .unwrap()
+                .to_string(),
+            "[one, two]"
+        );
+        call_native_in(&interpreter, None, "removeFile", [file]).unwrap();
+        assert_eq!(
+            call_native_in(&interpreter, None, "exists", [file]).unwrap(),
+            Literal::Boolean(false)
+        );
+        assert!(matches!(
+            call_native_in(&interpreter, None, "readFile", [file]),
+            Err(LoxResult::SystemError { .. })
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_scoped_access_rejects_paths_outside_root() {
+        let dir = scratch_dir("scoped");
+        let mut interpreter = Interpreter::new();
+        interpreter.set_fs_access(FsAccess::scoped(dir.to_str().unwrap()).unwrap());
+
+        let inside = dir.join("inside.txt");
+        let escape = dir.join("..").join("outside.txt");
+        assert!(call_native_in(&interpreter, None, "exists", [inside.to_str().unwrap()]).is_ok());
+        assert!(matches!(
+            call_native_in(&interpreter, None, "exists", [escape.to_str().unwrap()]),
+            Err(LoxResult::RuntimeError { .. })
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scoped_access_rejects_dangling_symlinks() {
+        let dir = scratch_dir("dangling");
+        let outside = scratch_dir("dangling-target");
+        let target = outside.join("escaped.txt");
+        let link = dir.join("link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_fs_access(FsAccess::scoped(dir.to_str().unwrap()).unwrap());
+
+        assert!(matches!(
+            call_native_in(
+                &interpreter,
+                None,
+                "writeFile",
+                [link.to_str().unwrap(), "pwned"]
+            ),
+            Err(LoxResult::RuntimeError { .. })
+        ));
+        assert!(!target.exists());
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(outside).unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+//! Natives taking Lox functions as arguments. Callbacks run through the
+//! interpreter like any other call, so `return` works as usual and runtime
+//! errors raised inside them abort the whole native call.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(
+        NativeFunction::new("map", |interpreter, args| {
+            let function = args.function(1)?;
+            let values = args
+                .list(0)?
+                .iter()
+                .map(|value| args.call(interpreter, &function, vec![value.clone()]))
+                .collect::<Result<_, _>>()?;
+            Ok(Literal::List(Rc::new(values)))
+        })
+        .param("values", ParamType::List)
+        .param("function", ParamType::Function),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("filter", |interpreter, args| {
+            let predicate = args.function(1)?;
+            let mut kept = Vec::new();
+            for value in args.list(0)?.iter() {
+                let verdict = args.call(interpreter, &predicate, vec![value.clone()])?;
+                if interpreter.is_truthy(&verdict) {
+                    kept.push(value.clone());
+                }
+            }
+            Ok(Literal::List(Rc::new(kept)))
+        })
+        .param("values", ParamType::List)
+        .param("predicate", ParamType::Function),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("reduce", |interpreter, args| {
+            let values = args.list(0)?;
+            let function = args.function(1)?;
+            let mut remaining = values.iter();
+            let mut accumulator = match args.get(2) {
+                Literal::Nil => match remaining.next() {
+                    Some(first) => first.clone(),
+                    None => {
+                        return Err(
+                            args.error("cannot reduce an empty list without an initial value")
+                        )
+                    }
+                },
+                initial => initial,
+            };
+            for value in remaining {
+                accumulator =
+                    args.call(interpreter, &function, vec![accumulator, value.clone()])?;
+            }
+            Ok(accumulator)
+        })
+        .param("values", ParamType::List)
+        .param("function", ParamType::Function)
+        .optional("initial", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("forEach", |interpreter, args| {
+            let function = args.function(1)?;
+            for value in args.list(0)?.iter() {
+                args.call(interpreter, &function, vec![value.clone()])?;
+            }
+            Ok(Literal::Nil)
+        })
+        .param("values", ParamType::List)
+        .param("function", ParamType::Function),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("sortBy", |interpreter, args| {
+            let values = args.list(0)?;
+            let key = args.function(1)?;
+            let keys = values
+                .iter()
+                .map(|value| args.call(interpreter, &key, vec![value.clone()]))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut order: Vec<usize> = (0..values.len()).collect();
+            let mut failure = None;
+            // Stable, so elements with equal keys keep their order.
+            order.sort_by(|a, b| {
+                compare(&keys[*a], &keys[*b]).unwrap_or_else(|| {
+                    failure.get_or_insert_with(|| sort_key_error(args, &keys[*a], &keys[*b]));
+                    Ordering::Equal
+                })
+            });
+            if let Some(error) = failure {
+                return Err(error);
+            }
+            let sorted = order.into_iter().map(|i| values[i].clone()).collect();
+            Ok(Literal::List(Rc::new(sorted)))
+        })
+        .param("values", ParamType::List)
+        .param("key", ParamType::Function),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("times", |interpreter, args| {
+            let count = args.integer(0)?;
+            if count < 0 {
+                return Err(args.error("count must not be negative"));
+            }
+            let function = args.function(1)?;
+            let results = (0..count)
+                .map(|i| args.call(interpreter, &function, vec![Literal::Number(i as f64)]))
+                .collect::<Result<_, _>>()?;
+            Ok(Literal::List(Rc::new(results)))
+        })
+        .param("count", ParamType::Number)
+        .param("function", ParamType::Function),
+    );
+}
+
+fn compare(a: &Literal, b: &Literal) -> Option<Ordering> {
+    match (a, b) {
+        (Literal::Number(x), Literal::Number(y)) => x.partial_cmp(y),
+        (Literal::String(x), Literal::String(y)) => Some(x.cmp(y)),
+        _ => None,
+    }
+}
+
+fn sort_key_error(args: &NativeArgs, a: &Literal, b: &Literal) -> LoxResult {
+    args.error(&format!(
+        "keys must be all numbers or all strings, cannot compare {a} with {b}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::SharedBuffer;
+
+    fn run(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_diagnostics(Box::new(SharedBuffer::new()));
+        let _ = interpreter.run(source);
+        interpreter
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> String {
+        interpreter
+            .globals
+            .borrow()
+            .get_value(name)
+            .map_or("undefined".to_string(), |value| value.to_string())
+    }
+
+    #[test]
+    fn test_callbacks_return_values() {
+        let interpreter = run("
+            fun double(x) { return x * 2; }
+            fun big(x) { return x > 1; }
+            fun add(a, b) { return a + b; }
+            var doubled = map([1, 2, 3], double);
+            var bigs = filter([1, 2, 3], big);
+            var sum = reduce([1, 2, 3], add);
+            var product = reduce([], add, 10);
+            fun square(i) { return i * i; }
+            var squares = times(3, square);
+        ");
+        assert_eq!(global(&interpreter, "doubled"), "[2, 4, 6]");
+        assert_eq!(global(&interpreter, "bigs"), "[2, 3]");
+        assert_eq!(global(&interpreter, "sum"), "6");
+        assert_eq!(global(&interpreter, "product"), "10");
+        assert_eq!(global(&interpreter, "squares"), "[0, 1, 4]");
+    }
+
+    #[test]
+    fn test_reduce_treats_nil_initial_as_absent() {
+        let interpreter = run("
+            fun add(a, b) { return a + b; }
+            var sum = reduce([1, 2, 3], add, nil);
+            var empty = reduce([], add, nil);
+        ");
+        assert_eq!(global(&interpreter, "sum"), "6");
+        assert_eq!(global(&interpreter, "empty"), "undefined");
+    }
+
+    #[test]
+    fn test_sort_by_is_stable() {
+        let interpreter = run("
+            fun length(word) { return len(word); }
+            var sorted = sortBy([\"ccc\", \"a\", \"bb\", \"d\"], length);
+        ");
+        assert_eq!(global(&interpreter, "sorted"), "[a, d, bb, ccc]");
+    }
+
+    #[test]
+    fn test_for_each_sees_side_effects_and_errors_propagate() {
+        let interpreter = run("
+            var total = 0;
+            fun add(x) { total = total + x; }
+            forEach([1, 2, 3], add);
+            fun boom(x) { return x + nil; }
+            var after = map([1], boom);
+        ");
+        assert_eq!(global(&interpreter, "total"), "6");
+        assert_eq!(global(&interpreter, "after"), "undefined");
+    }
+
+    #[test]
+    fn test_callback_arity_mismatch_names_callback() {
+        let interpreter = run("fun pair(a, b) { return a; }");
+        let Some(Literal::Func(map)) = interpreter.globals.borrow().get_value("map") else {
+            panic!("map is not registered");
+        };
+        let pair = interpreter.globals.borrow().get_value("pair").unwrap();
+        let values = Literal::List(Rc::new(vec![Literal::Number(1.0)]));
+        let result = crate::callable::LoxCallable::call(&map, &interpreter, vec![values, pair]);
+        assert!(matches!(
+            result,
+            Err(LoxResult::RuntimeError { message, .. })
+                if message == "map(): callback 'pair' expects 2 arguments, received 1"
+        ));
+    }
+}
@@ -1,18 +1,52 @@
 use crate::callable::*;
+use crate::conversion_functions;
 use crate::environment::*;
-use crate::error::LoxResult;
+use crate::error::{Frame, LoxResult};
 use crate::expr::*;
+use crate::format_functions;
+use crate::fs_functions::{self, FsAccess};
+use crate::higher_order_functions;
+use crate::interrupt::InterruptHandle;
+use crate::io_functions;
+use crate::json_functions;
+use crate::limits::{self, Limits};
 use crate::literal::*;
 use crate::lox_function::*;
-use crate::native_functions::*;
+use crate::math_functions;
+use crate::namespace::Namespace;
+use crate::native_functions::{self, NativeFunction};
+use crate::output::SharedBuffer;
+use crate::parser::Parser;
+use crate::process_functions;
+use crate::random::Rng;
+use crate::random_functions;
+use crate::regex_functions;
+use crate::scanner::Scanner;
 use crate::stmt::*;
+use crate::string_functions;
+use crate::time_functions;
+use crate::token::Token;
 use crate::token_type::*;
-use std::cell::RefCell;
+use crate::typecheck::TypeChecker;
+use std::cell::{RefCell, RefMut};
+use std::io::{self, Write};
 use std::rc::Rc;
+use std::time::Instant;
 pub struct Interpreter {
-    pub globals: Rc<RefCell<Environment>>,
+    pub(crate) globals: Rc<RefCell<Environment>>,
     environment: RefCell<Rc<RefCell<Environment>>>,
     in_loop: RefCell<usize>,
+    call_stack: RefCell<Vec<Frame>>,
+    assertions: bool,
+    fs_access: FsAccess,
+    process_access: bool,
+    rng: RefCell<Rng>,
+    output: RefCell<Box<dyn Write>>,
+    diagnostics: RefCell<Box<dyn Write>>,
+    limits: Limits,
+    steps: RefCell<u64>,
+    deadline: RefCell<Option<Instant>>,
+    interrupt: InterruptHandle,
 }
 
 impl StmtVisitor<()> for Interpreter {
@@ -37,8 +71,34 @@ impl StmtVisitor<()> for Interpreter {
     }
     fn visit_print_stmt(&self, expr: &StmtPrint) -> Result<(), LoxResult> {
         let value = self.evaluate(&expr.expression)?;
-        println!("{}", value);
-        Ok(())
+        writeln!(self.output(), "{}", value)
+            .map_err(|e| LoxResult::system_error(&format!("Cannot write output: {e}")))
+    }
+    fn visit_assert_stmt(&self, stmt: &StmtAssert) -> Result<(), LoxResult> {
+        if !self.assertions {
+            return Ok(());
+        }
+        let (passed, operands) = match &stmt.condition {
+            Expr::Binary(binary) if Interpreter::is_comparison(&binary.operator) => {
+                let left = self.evaluate(&binary.left)?;
+                let right = self.evaluate(&binary.right)?;
+                let result = self.binary(&binary.operator, left.clone(), right.clone())?;
+                (self.is_truthy(&result), Some((left, right)))
+            }
+            condition => (self.is_truthy(&self.evaluate(condition)?), None),
+        };
+        if passed {
+            return Ok(());
+        }
+
+        let mut message = format!("Assertion failed: {}", stmt.source);
+        if let Some((left, right)) = operands {
+            message.push_str(&format!(" (left: {left}, right: {right})"));
+        }
+        if let Some(detail) = &stmt.message {
+            message.push_str(&format!(": {}", self.evaluate(detail)?));
+        }
+        Err(LoxResult::interp_error(&stmt.keyword, &message))
     }
     fn visit_return_stmt(&self, stmt: &StmtReturn) -> Result<(), LoxResult> {
         if let Some(value) = &stmt.value {
@@ -60,6 +120,17 @@ impl StmtVisitor<()> for Interpreter {
             .define(expr.name.as_string(), value);
         Ok(())
     }
+    fn visit_destructure_stmt(&self, stmt: &StmtDestructure) -> Result<(), LoxResult> {
+        let value = self.evaluate(&stmt.initializer)?;
+        let values = self.unpack(&stmt.bracket, stmt.names.len(), &value)?;
+        for (name, value) in stmt.names.iter().zip(values) {
+            self.environment
+                .borrow()
+                .borrow_mut()
+                .define(name.as_string(), value);
+        }
+        Ok(())
+    }
     fn visit_if_stmt(&self, stmt: &StmtIf) -> Result<(), LoxResult> {
         if self.is_truthy(&self.evaluate(&stmt.condition)?) {
             self.execute(&stmt.then_branch)
@@ -72,15 +143,9 @@ impl StmtVisitor<()> for Interpreter {
 
     fn visit_while_stmt(&self, stmt: &StmtWhile) -> Result<(), LoxResult> {
         *self.in_loop.borrow_mut() += 1;
-        while self.is_truthy(&self.evaluate(&stmt.condition)?) {
-            match self.execute(&stmt.body) {
-                Err(LoxResult::Break) => break,
-                Err(e) => return Err(e),
-                Ok(_) => {}
-            }
-        }
+        let result = self.run_loop(stmt);
         *self.in_loop.borrow_mut() -= 1;
-        Ok(())
+        result
     }
     fn visit_function_stmt(&self, stmt: &StmtFunction) -> Result<(), LoxResult> {
         let function = LoxFunction::new(stmt);
@@ -97,21 +162,10 @@ impl StmtVisitor<()> for Interpreter {
 impl ExprVisitor<Literal> for Interpreter {
     fn visit_call_expr(&self, expr: &ExprCall) -> Result<Literal, LoxResult> {
         let callee = self.evaluate(&expr.callee)?;
-        let mut arguments = Vec::new();
-        for argument in &expr.arguments {
-            arguments.push(self.evaluate(argument)?);
-        }
+        let arguments = self.evaluate_elements(&expr.arguments)?;
 
         if let Literal::Func(callable) = callee {
-            let arity = callable.arity();
-            let received = arguments.len();
-            if arity != received {
-                return Err(LoxResult::interp_error(
-                    &expr.paren,
-                    "Expected {arity}, received {received} ",
-                ));
-            }
-            callable.call(self, arguments)
+            self.call_function(&callable, arguments, &expr.paren)
         } else {
             Err(LoxResult::interp_error(
                 &expr.paren,
@@ -120,6 +174,28 @@ impl ExprVisitor<Literal> for Interpreter {
         }
     }
 
+    fn visit_list_expr(&self, expr: &ExprList) -> Result<Literal, LoxResult> {
+        Ok(Literal::List(Rc::new(
+            self.evaluate_elements(&expr.elements)?,
+        )))
+    }
+
+    fn visit_spread_expr(&self, expr: &ExprSpread) -> Result<Literal, LoxResult> {
+        Err(LoxResult::interp_error(
+            &expr.ellipsis,
+            "Spread is only allowed in argument lists and list literals",
+        ))
+    }
+
+    fn visit_unpack_expr(&self, expr: &ExprUnpack) -> Result<Literal, LoxResult> {
+        let value = self.evaluate(&expr.value)?;
+        let values = self.unpack(&expr.bracket, expr.names.len(), &value)?;
+        for (name, value) in expr.names.iter().zip(values) {
+            self.environment.borrow().borrow_mut().assign(name, value)?;
+        }
+        Ok(value)
+    }
+
     fn visit_logical_expr(&self, expr: &ExprLogical) -> Result<Literal, LoxResult> {
         let left = self.evaluate(&expr.left)?;
 
@@ -150,32 +226,428 @@ impl ExprVisitor<Literal> for Interpreter {
     fn visit_binary_expr(&self, expr: &ExprBinary) -> Result<Literal, LoxResult> {
         let left = self.evaluate(&expr.left)?;
         let right = self.evaluate(&expr.right)?;
+        self.binary(&expr.operator, left, right)
+    }
+    fn visit_get_expr(&self, expr: &ExprGet) -> Result<Literal, LoxResult> {
+        match self.evaluate(&expr.object)? {
+            Literal::Namespace(namespace) => {
+                namespace.get(expr.name.as_string()).ok_or_else(|| {
+                    LoxResult::interp_error(
+                        &expr.name,
+                        &format!(
+                            "Undefined property '{}' in namespace {}",
+                            expr.name.as_string(),
+                            namespace.name()
+                        ),
+                    )
+                })
+            }
+            Literal::UserData(object) => object.get(expr.name.as_string()).ok_or_else(|| {
+                LoxResult::interp_error(
+                    &expr.name,
+                    &format!(
+                        "Undefined property '{}' on {}",
+                        expr.name.as_string(),
+                        object
+                    ),
+                )
+            }),
+            _ => Err(LoxResult::interp_error(
+                &expr.name,
+                "Only namespaces and host objects have properties",
+            )),
+        }
+    }
+    fn visit_grouping_expr(&self, expr: &ExprGrouping) -> Result<Literal, LoxResult> {
+        self.evaluate(&expr.expression)
+    }
+
+    fn visit_literal_expr(&self, expr: &ExprLiteral) -> Result<Literal, LoxResult> {
+        if let Some(value) = &expr.value {
+            Ok(value.clone())
+        } else {
+            Err(LoxResult::error(0, "Cannot evaluate expression"))
+        }
+    }
+
+    fn visit_unary_expr(&self, expr: &ExprUnary) -> Result<Literal, LoxResult> {
+        let right = self.evaluate(&expr.right)?;
 
         match expr.operator.token_type() {
+            TokenType::Minus => match right {
+                Literal::Number(x) => Ok(Literal::Number(-x)),
+                _ => Ok(Literal::Nil),
+            },
+            TokenType::Bang => Ok(Literal::Boolean(!self.is_truthy(&right))),
+            TokenType::Tilde => {
+                let value = Interpreter::integer(&expr.operator, &right)?;
+                Ok(Literal::Number((!value) as f64))
+            }
+            _ => Err(LoxResult::interp_error(
+                &expr.operator,
+                "Mismatch type to operator",
+            )),
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+
+        let interpreter = Interpreter {
+            globals: Rc::clone(&globals),
+            environment: RefCell::new(Rc::clone(&globals)),
+            in_loop: RefCell::new(0),
+            call_stack: RefCell::new(Vec::new()),
+            assertions: true,
+            fs_access: FsAccess::Denied,
+            process_access: false,
+            rng: RefCell::new(Rng::from_time()),
+            output: RefCell::new(Box::new(io::stdout())),
+            diagnostics: RefCell::new(Box::new(io::stderr())),
+            limits: Limits::default(),
+            steps: RefCell::new(0),
+            deadline: RefCell::new(None),
+            interrupt: InterruptHandle::default(),
+        };
+        native_functions::register(&interpreter);
+        math_functions::register(&interpreter);
+        string_functions::register(&interpreter);
+        fs_functions::register(&interpreter);
+        io_functions::register(&interpreter);
+        json_functions::register(&interpreter);
+        regex_functions::register(&interpreter);
+        time_functions::register(&interpreter);
+        random_functions::register(&interpreter);
+        process_functions::register(&interpreter);
+        higher_order_functions::register(&interpreter);
+        conversion_functions::register(&interpreter);
+        format_functions::register(&interpreter);
+        interpreter
+    }
+
+    pub fn define_global(&self, name: &str, value: Literal) {
+        self.globals.borrow_mut().define(name, value);
+    }
+
+    pub fn global(&self, name: &str) -> Option<Literal> {
+        self.globals.borrow().get_value(name)
+    }
+
+    pub fn register_native(&self, native: NativeFunction) {
+        let name = native.name().to_string();
+        self.globals.borrow_mut().define(
+            &name,
+            Literal::Func(Callable {
+                func: Rc::new(native),
+            }),
+        );
+    }
+
+    /// Returns the global namespace with the given name, creating it first
+    /// if needed.
+    pub fn namespace(&self, name: &str) -> Namespace {
+        if let Some(Literal::Namespace(existing)) = self.globals.borrow().get_value(name) {
+            return existing;
+        }
+        let namespace = Namespace::new(name);
+        self.globals
+            .borrow_mut()
+            .define(name, Literal::Namespace(namespace.clone()));
+        namespace
+    }
+
+    /// Calls `callable` after checking the number of arguments, recording
+    /// `site` as the call site for errors raised inside.
+    pub fn call_function(
+        &self,
+        callable: &Callable,
+        arguments: Vec<Literal>,
+        site: &Token,
+    ) -> Result<Literal, LoxResult> {
+        if self
+            .limits
+            .max_depth
+            .is_some_and(|max| self.call_stack.borrow().len() >= max)
+        {
+            return Err(LoxResult::interp_error(site, "Stack overflow"));
+        }
+        self.check_budget(site)?;
+        if let Some(expected) = arity_mismatch(callable, arguments.len()) {
+            return Err(LoxResult::interp_error(
+                site,
+                &format!(
+                    "Expected {expected} arguments, received {}",
+                    arguments.len()
+                ),
+            ));
+        }
+        self.call_stack.borrow_mut().push(Frame {
+            function: callable.name(),
+            site: site.clone(),
+        });
+        let result = limits::grow_stack(|| callable.call(self, arguments));
+        let frame = self.call_stack.borrow_mut().pop();
+        match (result, frame) {
+            (
+                Err(LoxResult::RuntimeError {
+                    token,
+                    message,
+                    mut trace,
+                }),
+                Some(frame),
+            ) => {
+                trace.push(frame);
+                Err(LoxResult::RuntimeError {
+                    token,
+                    message,
+                    trace,
+                })
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Calls the global function `name` from host code. Errors raised at the
+    /// call itself point at a synthetic token holding `name`.
+    pub fn call_global(&self, name: &str, arguments: Vec<Literal>) -> Result<Literal, LoxResult> {
+        let site = Token::new(TokenType::Identifier, name.to_string(), 0, None);
+        self.start_budget();
+        let callee = self.globals.borrow().get_value(name);
+        let result = match callee {
+            Some(Literal::Func(callable)) => self.call_function(&callable, arguments, &site),
+            Some(other) => Err(LoxResult::interp_error(
+                &site,
+                &format!(
+                    "'{name}' is a {}, not a function",
+                    conversion_functions::type_name(&other)
+                ),
+            )),
+            None => Err(LoxResult::interp_error(
+                &site,
+                &format!("Undefined variable: {name}"),
+            )),
+        };
+        self.finish_budget();
+        result
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Handle stopping the current evaluation, e.g. from a Ctrl-C handler.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    // Budgets cover a whole evaluation, so they are only reset when entered
+    // from the host, not from a native calling back into Lox.
+    fn start_budget(&self) {
+        if !self.call_stack.borrow().is_empty() {
+            return;
+        }
+        *self.steps.borrow_mut() = 0;
+        *self.deadline.borrow_mut() = self
+            .limits
+            .max_duration
+            .map(|duration| Instant::now() + duration);
+    }
+
+    // An interrupt requested before an evaluation starts stops it at once;
+    // one that arrives while it runs is spent when it ends, however it ends.
+    fn finish_budget(&self) {
+        if self.call_stack.borrow().is_empty() {
+            self.interrupt.clear();
+        }
+    }
+
+    fn check_budget(&self, token: &Token) -> Result<(), LoxResult> {
+        if self.interrupt.is_interrupted() {
+            return Err(LoxResult::interp_error(token, "Interrupted"));
+        }
+        if let Some(max) = self.limits.max_steps {
+            if *self.steps.borrow() > max {
+                return Err(LoxResult::interp_error(
+                    token,
+                    &format!("Step limit of {max} statements exceeded"),
+                ));
+            }
+        }
+        if let (Some(deadline), Some(duration)) =
+            (*self.deadline.borrow(), self.limits.max_duration)
+        {
+            if Instant::now() >= deadline {
+                return Err(LoxResult::interp_error(
+                    token,
+                    &format!("Time limit of {} ms exceeded", duration.as_millis()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn run_loop(&self, stmt: &StmtWhile) -> Result<(), LoxResult> {
+        while self.is_truthy(&self.evaluate(&stmt.condition)?) {
+            self.check_budget(&stmt.keyword)?;
+            match self.execute(&stmt.body) {
+                Err(LoxResult::Break) => break,
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Token of the innermost call being evaluated, used by natives to
+    /// point their errors at the call site.
+    pub fn call_site(&self) -> Token {
+        self.call_stack
+            .borrow()
+            .last()
+            .map(|frame| frame.site.clone())
+            .unwrap_or_else(|| Token::eof(0))
+    }
+
+    /// The calls in progress, outermost first.
+    pub fn call_stack(&self) -> Vec<Frame> {
+        self.call_stack.borrow().clone()
+    }
+
+    pub fn set_assertions(&mut self, enabled: bool) {
+        self.assertions = enabled;
+    }
+
+    pub fn set_fs_access(&mut self, access: FsAccess) {
+        self.fs_access = access;
+    }
+
+    pub fn fs_access(&self) -> &FsAccess {
+        &self.fs_access
+    }
+
+    /// Lets scripts read and change the environment variables and working
+    /// directory of the host process and end the evaluation with `exit()`.
+    /// Disabled by default.
+    pub fn set_process_access(&mut self, enabled: bool) {
+        self.process_access = enabled;
+    }
+
+    pub fn process_access(&self) -> bool {
+        self.process_access
+    }
+
+    /// Restarts the random number generator used by `random()` and friends.
+    pub fn seed(&self, seed: u64) {
+        *self.rng.borrow_mut() = Rng::new(seed);
+    }
+
+    pub(crate) fn rng(&self) -> RefMut<'_, Rng> {
+        self.rng.borrow_mut()
+    }
+
+    /// Replaces the sink `print` and the output natives write to (stdout
+    /// by default).
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = RefCell::new(output);
+    }
+
+    /// Replaces the sink errors are reported to (stderr by default).
+    pub fn set_diagnostics(&mut self, diagnostics: Box<dyn Write>) {
+        self.diagnostics = RefCell::new(diagnostics);
+    }
+
+    /// Redirects output and diagnostics to new in-memory buffers and returns
+    /// them, in that order.
+    pub fn capture(&mut self) -> (SharedBuffer, SharedBuffer) {
+        let output = SharedBuffer::new();
+        let diagnostics = SharedBuffer::new();
+        self.set_output(Box::new(output.clone()));
+        self.set_diagnostics(Box::new(diagnostics.clone()));
+        (output, diagnostics)
+    }
+
+    pub fn output(&self) -> RefMut<'_, dyn Write> {
+        RefMut::map(self.output.borrow_mut(), |output| output.as_mut())
+    }
+
+    /// Writes the error to the diagnostics sink.
+    pub fn report(&self, error: &LoxResult) {
+        let mut diagnostics = self.diagnostics.borrow_mut();
+        let _ = error.report(diagnostics.as_mut());
+        let _ = diagnostics.flush();
+    }
+
+    /// Scans, parses, type checks and runs `source`, reporting every error
+    /// to the diagnostics sink. Returns the value of the last statement when
+    /// it is an expression, or of a top-level `return`, otherwise `nil`; or
+    /// the first error.
+    pub fn run(&self, source: &str) -> Result<Literal, LoxResult> {
+        let result = self.run_source(source);
+        let _ = self.output().flush();
+        result.map_err(|errors| {
+            for error in &errors {
+                self.report(error);
+            }
+            errors.into_iter().next().expect("failures carry an error")
+        })
+    }
+
+    fn run_source(&self, source: &str) -> Result<Literal, Vec<LoxResult>> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens()?;
+        let statements = Parser::new(tokens).parse()?;
+        TypeChecker::new().check(&statements)?;
+        self.interprete(&statements).map_err(|e| vec![e])
+    }
+
+    pub(crate) fn execute_block(
+        &self,
+        statements: &[Stmt],
+        env: Environment,
+    ) -> Result<(), LoxResult> {
+        let previous = self.environment.replace(Rc::new(RefCell::new(env)));
+
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+
+        self.environment.replace(previous);
+        result
+    }
+
+    fn binary(
+        &self,
+        operator: &Token,
+        left: Literal,
+        right: Literal,
+    ) -> Result<Literal, LoxResult> {
+        match operator.token_type() {
             TokenType::Minus => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Number(x - y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsupported operands",
-                )),
+                _ => Err(LoxResult::interp_error(operator, "Unsupported operands")),
             },
             TokenType::Slash => match (left, right) {
-                (Literal::Number(_), Literal::Number(0.0)) => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Cannot divide by 0",
-                )),
+                (Literal::Number(_), Literal::Number(0.0)) => {
+                    Err(LoxResult::interp_error(operator, "Cannot divide by 0"))
+                }
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Number(x / y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsupported operands",
-                )),
+                _ => Err(LoxResult::interp_error(operator, "Unsupported operands")),
             },
             TokenType::Star => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Number(x * y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsuported operands",
-                )),
+                _ => Err(LoxResult::interp_error(operator, "Unsuported operands")),
             },
             TokenType::Plus => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Number(x + y)),
@@ -188,43 +660,35 @@ impl ExprVisitor<Literal> for Interpreter {
                 (Literal::Number(x), Literal::String(y)) => {
                     Ok(Literal::String(format!("{}{}", x, y)))
                 }
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsupported operands",
-                )),
+                _ => Err(LoxResult::interp_error(operator, "Unsupported operands")),
             },
             TokenType::Greater => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Boolean(x > y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsuported operands",
-                )),
+                (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(x > y)),
+                _ => Err(LoxResult::interp_error(operator, "Unsuported operands")),
             },
             TokenType::GreaterEqual => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Boolean(x >= y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsuported operands",
-                )),
+                (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(x >= y)),
+                _ => Err(LoxResult::interp_error(operator, "Unsuported operands")),
             },
             TokenType::Less => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Boolean(x < y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsuported operands",
-                )),
+                (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(x < y)),
+                _ => Err(LoxResult::interp_error(operator, "Unsuported operands")),
             },
             TokenType::LessEqual => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Boolean(x <= y)),
-                _ => Err(LoxResult::interp_error(
-                    &expr.operator,
-                    "Unsuported operands",
-                )),
+                (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(x <= y)),
+                _ => Err(LoxResult::interp_error(operator, "Unsuported operands")),
             },
             TokenType::BangEqual => match (left, right) {
                 (Literal::Number(x), Literal::Number(y)) => Ok(Literal::Boolean(x != y)),
                 (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(!x.eq(&y))),
                 (Literal::Boolean(x), Literal::Boolean(y)) => Ok(Literal::Boolean(x != y)),
+                (Literal::Nil, Literal::Nil) => Ok(Literal::Boolean(false)),
+                (Literal::List(x), Literal::List(y)) => Ok(Literal::Boolean(x != y)),
+                (Literal::UserData(x), Literal::UserData(y)) => Ok(Literal::Boolean(x != y)),
                 _ => Ok(Literal::Boolean(true)),
             },
             TokenType::Equals => match (left, right) {
@@ -232,100 +696,190 @@ impl ExprVisitor<Literal> for Interpreter {
                 (Literal::String(x), Literal::String(y)) => Ok(Literal::Boolean(x.eq(&y))),
                 (Literal::Boolean(x), Literal::Boolean(y)) => Ok(Literal::Boolean(x == y)),
                 (Literal::Nil, Literal::Nil) => Ok(Literal::Boolean(true)),
+                (Literal::List(x), Literal::List(y)) => Ok(Literal::Boolean(x == y)),
+                (Literal::UserData(x), Literal::UserData(y)) => Ok(Literal::Boolean(x == y)),
                 _ => Ok(Literal::Boolean(false)),
             },
+            TokenType::Ampersand
+            | TokenType::Bar
+            | TokenType::Caret
+            | TokenType::LeftShift
+            | TokenType::RightShift => self.bitwise(operator, &left, &right),
 
             _ => {
                 todo!("not implemented")
             }
         }
     }
-    fn visit_grouping_expr(&self, expr: &ExprGrouping) -> Result<Literal, LoxResult> {
-        self.evaluate(&expr.expression)
-    }
-
-    fn visit_literal_expr(&self, expr: &ExprLiteral) -> Result<Literal, LoxResult> {
-        if let Some(value) = &expr.value {
-            Ok(value.clone())
-        } else {
-            Err(LoxResult::error(0, "Cannot evaluate expression"))
-        }
-    }
-
-    fn visit_unary_expr(&self, expr: &ExprUnary) -> Result<Literal, LoxResult> {
-        let right = self.evaluate(&expr.right)?;
 
-        match expr.operator.token_type() {
-            TokenType::Minus => match right {
-                Literal::Number(x) => Ok(Literal::Number(-x)),
-                _ => Ok(Literal::Nil),
-            },
-            TokenType::Bang => Ok(Literal::Boolean(!self.is_truthy(&right))),
-            _ => Err(LoxResult::RuntimeError {
-                token: expr.operator.clone(),
-                message: "Mismatch type to operator".to_string(),
-            }),
-        }
+    fn bitwise(
+        &self,
+        operator: &Token,
+        left: &Literal,
+        right: &Literal,
+    ) -> Result<Literal, LoxResult> {
+        let x = Interpreter::integer(operator, left)?;
+        let y = Interpreter::integer(operator, right)?;
+        let result = match operator.token_type() {
+            TokenType::Ampersand => x & y,
+            TokenType::Bar => x | y,
+            TokenType::Caret => x ^ y,
+            _ => {
+                let shift = u32::try_from(y)
+                    .ok()
+                    .filter(|shift| *shift < 64)
+                    .ok_or_else(|| {
+                        LoxResult::interp_error(operator, "Shift amount must be between 0 and 63")
+                    })?;
+                if operator.is(TokenType::LeftShift) {
+                    x << shift
+                } else {
+                    x >> shift
+                }
+            }
+        };
+        Ok(Literal::Number(result as f64))
     }
-}
 
-impl Default for Interpreter {
-    fn default() -> Self {
-        Self::new()
+    fn integer(operator: &Token, value: &Literal) -> Result<i64, LoxResult> {
+        i64::try_from(value.clone()).map_err(|_| {
+            LoxResult::interp_error(
+                operator,
+                &format!("Operands of '{}' must be integers", operator.as_string()),
+            )
+        })
     }
-}
-
-impl Interpreter {
-    pub fn new() -> Interpreter {
-        let globals = Rc::new(RefCell::new(Environment::new()));
-
-        globals.borrow_mut().define(
-            "clock",
-            Literal::Func(Callable {
-                func: Rc::new(LoxClock {}),
-            }),
-        );
 
-        Interpreter {
-            globals: Rc::clone(&globals),
-            environment: RefCell::new(Rc::clone(&globals)),
-            in_loop: RefCell::new(0),
+    fn evaluate_elements(&self, exprs: &[Expr]) -> Result<Vec<Literal>, LoxResult> {
+        let mut values = Vec::new();
+        for expr in exprs {
+            if let Expr::Spread(spread) = expr {
+                match self.evaluate(&spread.expression)? {
+                    Literal::List(elements) => values.extend(elements.iter().cloned()),
+                    other => {
+                        return Err(LoxResult::interp_error(
+                            &spread.ellipsis,
+                            &format!("Can only spread a list, got {other}"),
+                        ))
+                    }
+                }
+            } else {
+                values.push(self.evaluate(expr)?);
+            }
         }
+        Ok(values)
     }
 
-    pub fn execute_block(&self, statements: &[Stmt], env: Environment) -> Result<(), LoxResult> {
-        let previous = self.environment.replace(Rc::new(RefCell::new(env)));
-
-        let result = statements
-            .iter()
-            .try_for_each(|statement| self.execute(statement));
-
-        self.environment.replace(previous);
-        result
+    fn unpack(
+        &self,
+        bracket: &Token,
+        targets: usize,
+        value: &Literal,
+    ) -> Result<Vec<Literal>, LoxResult> {
+        match value {
+            Literal::List(values) if values.len() == targets => Ok(values.to_vec()),
+            Literal::List(values) => Err(LoxResult::interp_error(
+                bracket,
+                &format!(
+                    "Expected {} values to unpack, received {}",
+                    targets,
+                    values.len()
+                ),
+            )),
+            other => Err(LoxResult::interp_error(
+                bracket,
+                &format!("Can only destructure a list, got {other}"),
+            )),
+        }
     }
 
     fn evaluate(&self, expr: &Expr) -> Result<Literal, LoxResult> {
         expr.accept(self)
     }
     fn execute(&self, stmt: &Stmt) -> Result<(), LoxResult> {
+        *self.steps.borrow_mut() += 1;
         stmt.accept(self)
     }
-    fn is_truthy(&self, literal: &Literal) -> bool {
+    fn is_comparison(operator: &Token) -> bool {
+        matches!(
+            operator.token_type(),
+            TokenType::Equals
+                | TokenType::BangEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+        )
+    }
+    pub fn is_truthy(&self, literal: &Literal) -> bool {
         !matches!(literal, Literal::Nil | Literal::Boolean(false))
     }
 
-    pub fn interprete(&self, statements: &[Stmt]) -> bool {
-        let mut success = true;
-        for statement in statements {
-            if self.execute(statement).is_err() {
-                success = false;
-                break;
-            }
+    /// Executes the statements, stopping at the first runtime error. The
+    /// result is the value of the last statement when it is an expression
+    /// statement, the value of a top-level `return`, or `nil`.
+    pub(crate) fn interprete(&self, statements: &[Stmt]) -> Result<Literal, LoxResult> {
+        self.start_budget();
+        let result = match statements.split_last() {
+            Some((Stmt::Expression(last), init)) => init
+                .iter()
+                .try_for_each(|statement| self.execute(statement))
+                .and_then(|_| self.evaluate(&last.expression)),
+            _ => statements
+                .iter()
+                .try_for_each(|statement| self.execute(statement))
+                .map(|_| Literal::Nil),
+        };
+        self.finish_budget();
+        match result {
+            Err(LoxResult::Return { value }) => Ok(value),
+            result => result,
         }
-        success
     }
 }
 
+/// Runs `source` in a fresh interpreter and returns what it printed and what
+/// it reported as errors.
+#[cfg(test)]
+pub fn capture(source: &str) -> (String, String) {
+    let mut interpreter = Interpreter::new();
+    let (output, diagnostics) = interpreter.capture();
+    let _ = interpreter.run(source);
+    (output.take(), diagnostics.take())
+}
+
+/// Calls the native `name`, looked up in namespace `ns` or among the globals,
+/// with `arguments` in a fresh interpreter.
+#[cfg(test)]
+pub fn call_native<T: Into<Literal>>(
+    ns: Option<&str>,
+    name: &str,
+    arguments: impl IntoIterator<Item = T>,
+) -> Result<Literal, LoxResult> {
+    call_native_in(&Interpreter::new(), ns, name, arguments)
+}
+
+/// Like [`call_native`], in an interpreter the test has set up.
+#[cfg(test)]
+pub fn call_native_in<T: Into<Literal>>(
+    interpreter: &Interpreter,
+    ns: Option<&str>,
+    name: &str,
+    arguments: impl IntoIterator<Item = T>,
+) -> Result<Literal, LoxResult> {
+    let function = match ns {
+        Some(ns) => interpreter.namespace(ns).get(name),
+        None => interpreter.globals.borrow().get_value(name),
+    };
+    let Some(Literal::Func(function)) = function else {
+        panic!(
+            "{}{name} is not registered",
+            ns.map_or(String::new(), |ns| format!("{ns}."))
+        );
+    };
+    function.call(interpreter, arguments.into_iter().map(Into::into).collect())
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -362,6 +916,7 @@ mod tests {
         let var_name = make_var_identifier("my_var");
         let var_statement = Stmt::Var(StmtVar {
             name: var_name.clone(),
+            type_annotation: None,
             initializer: Some(*make_literal(Literal::Number(42.0))),
         });
         assert!(interp.execute(&var_statement).is_ok());
@@ -391,6 +946,7 @@ mod tests {
 
         let var_statement = Stmt::Var(StmtVar {
             name: var.clone(),
+            type_annotation: None,
             initializer: None,
         });
 
@@ -411,6 +967,7 @@ mod tests {
 
         let var_statement = Stmt::Var(StmtVar {
             name: var.clone(),
+            type_annotation: None,
             initializer: Some(*expr),
         });
 
@@ -607,6 +1164,20 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.ok(), Some(Literal::Boolean(true)));
     }
+
+    #[test]
+    fn test_nil_is_not_unequal_to_nil() {
+        let interp = Interpreter::new();
+        let binary_bang_equal = make_binary_expression(
+            make_literal(Literal::Nil),
+            make_literal(Literal::Nil),
+            make_token_operator(TokenType::BangEqual, "!="),
+        );
+
+        let result = interp.evaluate(&binary_bang_equal);
+
+        assert_eq!(result.ok(), Some(Literal::Boolean(false)));
+    }
     #[rstest]
     #[case::two_numbers(make_literal(Literal::Number(6.0)), make_literal(Literal::Number(6.0)))]
     #[case::two_strings (make_literal(Literal::String("abc".to_string())), make_literal(Literal::String("abc".to_string())))]
@@ -625,4 +1196,305 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.ok(), Some(Literal::Boolean(true)));
     }
+
+    fn make_assertion(left: f64, right: f64) -> Stmt {
+        Stmt::Assert(StmtAssert {
+            keyword: make_token_operator(TokenType::Assert, "assert"),
+            condition: make_binary_expression(
+                make_literal(Literal::Number(left)),
+                make_literal(Literal::Number(right)),
+                make_token_operator(TokenType::Equals, "=="),
+            ),
+            message: None,
+            source: format!("{left} == {right}"),
+        })
+    }
+
+    #[test]
+    fn test_assert_passes() {
+        let interp = Interpreter::new();
+        assert!(interp.execute(&make_assertion(1.0, 1.0)).is_ok());
+    }
+
+    #[test]
+    fn test_assert_failure_reports_operands() {
+        let interp = Interpreter::new();
+        let result = interp.execute(&make_assertion(1.0, 2.0));
+        assert!(matches!(
+            result,
+            Err(LoxResult::RuntimeError { message, .. })
+                if message == "Assertion failed: 1 == 2 (left: 1, right: 2)"
+        ));
+    }
+
+    #[test]
+    fn test_assert_disabled() {
+        let mut interp = Interpreter::new();
+        interp.set_assertions(false);
+        assert!(interp.execute(&make_assertion(1.0, 2.0)).is_ok());
+    }
+
+    fn make_list(values: &[f64]) -> Expr {
+        Expr::List(ExprList {
+            bracket: make_token_operator(TokenType::LeftBracket, "["),
+            elements: values
+                .iter()
+                .map(|value| *make_literal(Literal::Number(*value)))
+                .collect(),
+        })
+    }
+
+    fn make_destructure(names: &[&str], initializer: Expr) -> Stmt {
+        Stmt::Destructure(StmtDestructure {
+            bracket: make_token_operator(TokenType::LeftBracket, "["),
+            names: names.iter().map(|name| make_var_identifier(name)).collect(),
+            initializer,
+        })
+    }
+
+    #[test]
+    fn test_destructuring_defines_each_target() {
+        let interp = Interpreter::new();
+        let statement = make_destructure(&["a", "b"], make_list(&[1.0, 2.0]));
+        assert!(interp.execute(&statement).is_ok());
+
+        let env = interp.environment.borrow();
+        assert_eq!(
+            env.borrow().get(&make_var_identifier("a")).unwrap(),
+            Literal::Number(1.0)
+        );
+        assert_eq!(
+            env.borrow().get(&make_var_identifier("b")).unwrap(),
+            Literal::Number(2.0)
+        );
+    }
+
+    #[rstest]
+    #[case::too_few_values(make_list(&[1.0]))]
+    #[case::too_many_values(make_list(&[1.0, 2.0, 3.0]))]
+    #[case::not_a_sequence(*make_literal(Literal::Number(1.0)))]
+    fn test_destructuring_shape_mismatch(#[case] initializer: Expr) {
+        let interp = Interpreter::new();
+        let statement = make_destructure(&["a", "b"], initializer);
+        assert!(interp.execute(&statement).is_err());
+    }
+
+    #[test]
+    fn test_spread_in_list_literal() {
+        let interp = Interpreter::new();
+        let list = Expr::List(ExprList {
+            bracket: make_token_operator(TokenType::LeftBracket, "["),
+            elements: vec![
+                *make_literal(Literal::Number(0.0)),
+                Expr::Spread(ExprSpread {
+                    ellipsis: make_token_operator(TokenType::Ellipsis, "..."),
+                    expression: Box::new(make_list(&[1.0, 2.0])),
+                }),
+            ],
+        });
+        assert_eq!(
+            interp.evaluate(&list).unwrap().to_string(),
+            "[0, 1, 2]".to_string()
+        );
+    }
+
+    #[rstest]
+    #[case::and(TokenType::Ampersand, "&", 12.0, 10.0, 8.0)]
+    #[case::or(TokenType::Bar, "|", 12.0, 10.0, 14.0)]
+    #[case::xor(TokenType::Caret, "^", 12.0, 10.0, 6.0)]
+    #[case::left_shift(TokenType::LeftShift, "<<", 1.0, 4.0, 16.0)]
+    #[case::right_shift(TokenType::RightShift, ">>", -16.0, 2.0, -4.0)]
+    fn test_binary_bitwise(
+        #[case] ttype: TokenType,
+        #[case] lexeme: &str,
+        #[case] left: f64,
+        #[case] right: f64,
+        #[case] expected: f64,
+    ) {
+        let interp = Interpreter::new();
+        let binary = make_binary_expression(
+            make_literal(Literal::Number(left)),
+            make_literal(Literal::Number(right)),
+            make_token_operator(ttype, lexeme),
+        );
+        assert_eq!(
+            interp.evaluate(&binary).ok(),
+            Some(Literal::Number(expected))
+        );
+    }
+
+    #[rstest]
+    #[case::fractional(Literal::Number(1.5), Literal::Number(1.0))]
+    #[case::not_a_number(Literal::String("1".to_string()), Literal::Number(1.0))]
+    #[case::nan(Literal::Number(f64::NAN), Literal::Number(1.0))]
+    fn test_binary_bitwise_requires_integers(#[case] left: Literal, #[case] right: Literal) {
+        let interp = Interpreter::new();
+        let binary = make_binary_expression(
+            make_literal(left),
+            make_literal(right),
+            make_token_operator(TokenType::Ampersand, "&"),
+        );
+        assert!(interp.evaluate(&binary).is_err());
+    }
+
+    #[test]
+    fn test_unary_tilde() {
+        let interp = Interpreter::new();
+        let unary_tilde = Expr::Unary(ExprUnary {
+            operator: make_token_operator(TokenType::Tilde, "~"),
+            right: make_literal(Literal::Number(5.0)),
+        });
+        assert_eq!(
+            interp.evaluate(&unary_tilde).ok(),
+            Some(Literal::Number(-6.0))
+        );
+    }
+
+    #[rstest]
+    #[case::less(TokenType::Less, "<", "apple", "banana", true)]
+    #[case::greater(TokenType::Greater, ">", "apple", "banana", false)]
+    #[case::prefix_is_smaller(TokenType::Less, "<", "app", "apple", true)]
+    #[case::greater_equal(TokenType::GreaterEqual, ">=", "b", "b", true)]
+    #[case::less_equal(TokenType::LessEqual, "<=", "\u{e9}", "z", false)]
+    fn test_binary_string_comparison(
+        #[case] ttype: TokenType,
+        #[case] lexeme: &str,
+        #[case] left: &str,
+        #[case] right: &str,
+        #[case] expected: bool,
+    ) {
+        let interp = Interpreter::new();
+        let binary = make_binary_expression(
+            make_literal(Literal::String(left.to_string())),
+            make_literal(Literal::String(right.to_string())),
+            make_token_operator(ttype, lexeme),
+        );
+        assert_eq!(
+            interp.evaluate(&binary).ok(),
+            Some(Literal::Boolean(expected))
+        );
+    }
+
+    #[test]
+    fn test_print_writes_to_output_sink() {
+        let (output, diagnostics) = capture("print 1 + 2; write(\"a\"); printf(\"{:>3}\", 7);");
+        assert_eq!(output, "3\na  7");
+        assert_eq!(diagnostics, "");
+    }
+
+    #[test]
+    fn test_errors_are_written_to_diagnostics_sink() {
+        let (output, diagnostics) = capture("print \"before\"; print missing; print \"after\";");
+        assert_eq!(output, "before\n");
+        assert_eq!(
+            diagnostics,
+            "Line: 1 at 'missing' : Undefined variable: missing \n"
+        );
+    }
+
+    #[test]
+    fn test_every_scanner_error_is_reported() {
+        let (output, diagnostics) = capture("print 1; @ print 2; #");
+        assert_eq!(output, "");
+        assert_eq!(diagnostics.lines().count(), 2);
+    }
+    #[test]
+    fn test_runtime_errors_report_call_stack() {
+        let source = "fun inner(x) {\n  return x + missing;\n}\n\
+                      fun outer() {\n  return map([1], inner);\n}\n\
+                      outer();";
+        let (_, diagnostics) = capture(source);
+        assert_eq!(
+            diagnostics,
+            "Call stack (innermost last):\n  line 7: outer()\n  line 5: map()\n  \
+             line 5: inner()\nLine: 2 at 'missing' : Undefined variable: missing \n"
+        );
+    }
+
+    #[test]
+    fn test_long_call_stacks_are_elided() {
+        let source = "fun down(n) {\n  if (n == 0) return missing;\n  return down(n - 1);\n}\n\
+                      down(30);";
+        let (_, diagnostics) = capture(source);
+        let lines: Vec<&str> = diagnostics.lines().collect();
+        assert_eq!(lines.len(), 1 + 10 + 1 + 10 + 1);
+        assert_eq!(lines[1], "  line 5: down()");
+        assert_eq!(lines[11], "  ... 11 more calls");
+        assert_eq!(lines[21], "  line 3: down()");
+    }
+
+    fn run_limited(source: &str, limits: Limits) -> String {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_limits(limits);
+        match interpreter.run_source(source) {
+            Err(errors) => errors[0].to_string(),
+            Ok(value) => panic!("expected an error, got {value}"),
+        }
+    }
+
+    #[rstest]
+    #[case::recursion("fun f(n) { return f(n + 1); }\nf(0);")]
+    #[case::through_natives("fun f(n) { return map([n], f); }\nf(0);")]
+    #[case::nested_blocks(
+        "fun f(n) { if (true) { while (true) { { return [f(n + 1) + 1 * 2]; } } } }\nf(0);"
+    )]
+    fn test_deep_recursion_is_a_stack_overflow(#[case] source: &str) {
+        assert_eq!(
+            run_limited(source, Limits::default()),
+            "Line: 1, Error: Stack overflow"
+        );
+    }
+
+    #[test]
+    fn test_nesting_up_to_the_parser_limit_runs() {
+        let depth = 120;
+        let source = format!(
+            "{}print {}1{};{}",
+            "{ if (true) ".repeat(depth / 2),
+            "(".repeat(depth),
+            ")".repeat(depth),
+            "}".repeat(depth / 2)
+        );
+        assert_eq!(capture(&source), ("1\n".to_string(), String::new()));
+        let chain = format!("print 0{};", " + 1".repeat(250));
+        assert_eq!(capture(&chain), ("250\n".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let limits = Limits {
+            max_steps: Some(100),
+            ..Limits::default()
+        };
+        assert_eq!(
+            run_limited("var i = 0;\nwhile (true) { i = i + 1; }", limits),
+            "Line: 2, Error: Step limit of 100 statements exceeded"
+        );
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let limits = Limits {
+            max_duration: Some(std::time::Duration::from_millis(20)),
+            ..Limits::default()
+        };
+        assert_eq!(
+            run_limited("fun spin() { while (true) {} }\nspin();", limits),
+            "Line: 1, Error: Time limit of 20 ms exceeded"
+        );
+    }
+
+    #[test]
+    fn test_budget_is_reset_for_each_run() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_limits(Limits {
+            max_steps: Some(50),
+            ..Limits::default()
+        });
+        for _ in 0..3 {
+            assert!(interpreter
+                .run_source("for (var i = 0; i < 10; i = i + 1) {}")
+                .is_ok());
+        }
+    }
 }
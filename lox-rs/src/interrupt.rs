@@ -0,0 +1,29 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Aborts the evaluation running in an interpreter with an "Interrupted"
+/// runtime error. The handle is `Send`, so it can be triggered from another
+/// thread or a signal handler; the interpreter notices it on the next loop
+/// iteration or function call. Requested while the interpreter is idle, it
+/// stops the next evaluation as soon as that starts.
+///
+/// Natives are not interrupted: a script waiting in `readLine()` or
+/// `input()`, or running a slow native, stops only once that call returns.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.requested.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use std::io::{self, BufRead, Read};
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(NativeFunction::new("readLine", |interpreter, args| {
+        flush(interpreter, args)?;
+        read_line(&mut io::stdin().lock()).map_err(|e| failure(args, e))
+    }));
+
+    interpreter.register_native(NativeFunction::new("readAll", |interpreter, args| {
+        flush(interpreter, args)?;
+        let mut contents = String::new();
+        io::stdin()
+            .lock()
+            .read_to_string(&mut contents)
+            .map_err(|e| failure(args, e))?;
+        Ok(Literal::String(contents))
+    }));
+
+    interpreter.register_native(
+        NativeFunction::new("input", |interpreter, args| {
+            if let Some(prompt) = args.optional_string(0)? {
+                write!(interpreter.output(), "{prompt}").map_err(|e| failure(args, e))?;
+            }
+            flush(interpreter, args)?;
+            read_line(&mut io::stdin().lock()).map_err(|e| failure(args, e))
+        })
+        .optional("prompt", ParamType::String),
+    );
+}
+
+/// Reads one line without its terminator, or `nil` at end of input.
+fn read_line(reader: &mut impl BufRead) -> io::Result<Literal> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(Literal::Nil);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Literal::String(line))
+}
+
+// Output of `print` must be visible before the script blocks on stdin.
+fn flush(interpreter: &Interpreter, args: &NativeArgs) -> Result<(), LoxResult> {
+    interpreter.output().flush().map_err(|e| failure(args, e))
+}
+
+fn failure(args: &NativeArgs, error: io::Error) -> LoxResult {
+    LoxResult::system_error(&format!("{}(): {}", args.name(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_line_strips_terminators_and_ends_with_nil() {
+        let mut input = Cursor::new("first\r\nsecond\nlast");
+        let expected = ["first", "second", "last"];
+        for line in expected {
+            assert_eq!(
+                read_line(&mut input).unwrap(),
+                Literal::String(line.to_string())
+            );
+        }
+        assert_eq!(read_line(&mut input).unwrap(), Literal::Nil);
+    }
+
+    #[test]
+    fn test_empty_line_is_not_end_of_input() {
+        let mut input = Cursor::new("\n");
+        assert_eq!(
+            read_line(&mut input).unwrap(),
+            Literal::String(String::new())
+        );
+        assert_eq!(read_line(&mut input).unwrap(), Literal::Nil);
+    }
+}
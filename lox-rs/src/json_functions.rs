@@ -0,0 +1,164 @@
+//! The `json` namespace. JSON objects become namespaces, so their members are
+//! read with the usual `object.member` syntax; arrays become lists.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::namespace::Namespace;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::rc::Rc;
+
+// Integral numbers up to 2^53 are exact in an f64 and are written without a
+// fractional part.
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+pub fn register(interpreter: &Interpreter) {
+    let json = interpreter.namespace("json");
+
+    json.register_native(
+        NativeFunction::new("parse", |_, args| {
+            let value: Value = serde_json::from_str(&args.string(0)?)
+                .map_err(|e| args.error(&format!("invalid JSON: {e}")))?;
+            Ok(from_json(value))
+        })
+        .param("text", ParamType::String),
+    );
+
+    json.register_native(
+        NativeFunction::new("stringify", |_, args| {
+            let value = to_json(args, &args.get(0))?;
+            let indent = match args.optional_number(1)? {
+                Some(_) => args.integer(1)?,
+                None => 0,
+            };
+            if !(0..=16).contains(&indent) {
+                return Err(args.error("indent must be between 0 and 16"));
+            }
+            Ok(Literal::String(write(&value, indent as usize)))
+        })
+        .param("value", ParamType::Any)
+        .optional("indent", ParamType::Number),
+    );
+}
+
+fn from_json(value: Value) -> Literal {
+    match value {
+        Value::Null => Literal::Nil,
+        Value::Bool(x) => Literal::Boolean(x),
+        Value::Number(x) => Literal::Number(x.as_f64().unwrap_or(f64::NAN)),
+        Value::String(x) => Literal::String(x),
+        Value::Array(values) => Literal::List(Rc::new(values.into_iter().map(from_json).collect())),
+        Value::Object(members) => {
+            let object = Namespace::new("object");
+            for (name, value) in members {
+                object.define(&name, from_json(value));
+            }
+            Literal::Namespace(object)
+        }
+    }
+}
+
+fn to_json(args: &NativeArgs, value: &Literal) -> Result<Value, LoxResult> {
+    match value {
+        Literal::Nil => Ok(Value::Null),
+        Literal::Boolean(x) => Ok(Value::Bool(*x)),
+        Literal::String(x) => Ok(Value::String(x.clone())),
+        Literal::Number(x) if x.fract() == 0.0 && x.abs() <= MAX_SAFE_INTEGER => {
+            Ok(Value::Number(Number::from(*x as i64)))
+        }
+        Literal::Number(x) => Number::from_f64(*x)
+            .map(Value::Number)
+            .ok_or_else(|| args.error(&format!("cannot represent {x} in JSON"))),
+        Literal::List(values) => values
+            .iter()
+            .map(|value| to_json(args, value))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Literal::Namespace(namespace) => {
+            let mut members = Map::new();
+            for (name, value) in namespace.members() {
+                members.insert(name, to_json(args, &value)?);
+            }
+            Ok(Value::Object(members))
+        }
+        Literal::Func(_) | Literal::Identifier(_) | Literal::UserData(_) => Err(args.error(&format!(
+            "cannot convert {value} to JSON, only nil, booleans, numbers, strings, lists and objects are supported"
+        ))),
+    }
+}
+
+fn write(value: &Value, indent: usize) -> String {
+    if indent == 0 {
+        return value.to_string();
+    }
+    let indent = " ".repeat(indent);
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    value
+        .serialize(&mut serializer)
+        .expect("JSON values always serialize");
+    String::from_utf8(output).expect("serde_json writes UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native;
+
+    #[test]
+    fn test_parse_and_stringify_roundtrip() {
+        let text = r#"{"name":"lox","tags":["a",true,null],"version":1,"ratio":0.5}"#;
+        let value = call_native(Some("json"), "parse", vec![Literal::from(text)]).unwrap();
+        let Literal::Namespace(object) = &value else {
+            panic!("expected an object, got {value}");
+        };
+        assert_eq!(object.get("version"), Some(Literal::Number(1.0)));
+        assert_eq!(
+            call_native(Some("json"), "stringify", vec![value]).unwrap(),
+            Literal::from(r#"{"name":"lox","ratio":0.5,"tags":["a",true,null],"version":1}"#)
+        );
+    }
+
+    #[test]
+    fn test_stringify_with_indent() {
+        let list = Literal::List(Rc::new(vec![Literal::Number(1.0), Literal::Nil]));
+        assert_eq!(
+            call_native(Some("json"), "stringify", vec![list, Literal::Number(2.0)]).unwrap(),
+            Literal::from("[\n  1,\n  null\n]")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_reports_position() {
+        let result = call_native(Some("json"), "parse", vec![Literal::from("[1,\n 2 x]")]);
+        assert!(matches!(
+            result,
+            Err(LoxResult::RuntimeError { message, .. })
+                if message.starts_with("parse(): invalid JSON: ") && message.contains("at line 2 column")
+        ));
+    }
+
+    #[test]
+    fn test_functions_cannot_be_stringified() {
+        let interpreter = Interpreter::new();
+        let clock = interpreter.globals.borrow().get_value("clock").unwrap();
+        let result = call_native(
+            Some("json"),
+            "stringify",
+            vec![Literal::List(Rc::new(vec![clock]))],
+        );
+        assert!(matches!(
+            result,
+            Err(LoxResult::RuntimeError { message, .. })
+                if message.starts_with("stringify(): cannot convert fn Native::clock to JSON")
+        ));
+    }
+
+    #[test]
+    fn test_non_finite_numbers_are_rejected() {
+        assert!(call_native(Some("json"), "stringify", vec![Literal::Number(f64::NAN)]).is_err());
+    }
+}
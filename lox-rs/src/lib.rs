@@ -0,0 +1,275 @@
+//! A tree-walking interpreter for Lox.
+//!
+//! [`Lox`] is the entry point for embedding: it owns an [`Interpreter`] whose
+//! globals persist between calls, so a host can load a script once and then
+//! evaluate further source against it, the way the REPL does.
+//!
+//! ```no_run
+//! use lox_rs::{Literal, Lox};
+//!
+//! let lox = Lox::new();
+//! lox.define("limit", Literal::Number(10.0));
+//! lox.eval("fun allowed(n) { return n <= limit; }").unwrap();
+//! assert_eq!(lox.eval("allowed(3);").unwrap(), Literal::Boolean(true));
+//! ```
+
+// mod ast_print;
+mod conversion_functions;
+mod environment;
+mod expr;
+mod format_functions;
+mod fs_functions;
+mod higher_order_functions;
+mod io_functions;
+mod json_functions;
+mod lox_function;
+mod math_functions;
+mod parser;
+mod process_functions;
+mod random;
+mod random_functions;
+mod regex_functions;
+mod scanner;
+mod stmt;
+mod string_functions;
+mod time_functions;
+mod typecheck;
+
+pub mod callable;
+pub mod error;
+pub mod interpreter;
+pub mod interrupt;
+pub mod limits;
+pub mod literal;
+pub mod namespace;
+pub mod native_functions;
+pub mod output;
+pub mod token;
+pub mod token_type;
+pub mod userdata;
+
+pub use crate::error::LoxResult;
+pub use crate::fs_functions::FsAccess;
+pub use crate::interpreter::Interpreter;
+pub use crate::interrupt::InterruptHandle;
+pub use crate::limits::Limits;
+pub use crate::literal::Literal;
+pub use crate::namespace::Namespace;
+pub use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+pub use crate::output::SharedBuffer;
+pub use crate::userdata::{UserData, UserDataBuilder};
+
+use std::path::Path;
+
+/// An interpreter session. Errors are returned to the caller and also
+/// reported to the interpreter's diagnostics sink (stderr unless replaced
+/// with [`Interpreter::set_diagnostics`]).
+pub struct Lox {
+    interpreter: Interpreter,
+}
+
+impl Lox {
+    pub fn new() -> Lox {
+        Lox::with_interpreter(Interpreter::new())
+    }
+
+    /// Wraps an interpreter configured by the host (sinks, file system
+    /// access, extra natives).
+    pub fn with_interpreter(interpreter: Interpreter) -> Lox {
+        Lox { interpreter }
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+        &mut self.interpreter
+    }
+
+    /// Collects what scripts print and the errors they report in memory
+    /// instead of stdout and stderr. Returns the output and diagnostics
+    /// buffers.
+    pub fn capture(&mut self) -> (SharedBuffer, SharedBuffer) {
+        self.interpreter.capture()
+    }
+
+    /// Runs `source` and returns the value of its last statement when that
+    /// is an expression (e.g. `1 + 2;`) or of a top-level `return`, `nil`
+    /// otherwise.
+    pub fn eval(&self, source: &str) -> Result<Literal, LoxResult> {
+        self.interpreter.run(source)
+    }
+
+    pub fn eval_file(&self, path: impl AsRef<Path>) -> Result<Literal, LoxResult> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(source) => self.eval(&source),
+            Err(e) => {
+                let error =
+                    LoxResult::system_error(&format!("Cannot read '{}': {}", path.display(), e));
+                self.interpreter.report(&error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Handle aborting the evaluation in progress with an "Interrupted"
+    /// error, usable from other threads.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interpreter.interrupt_handle()
+    }
+
+    /// Calls the global function `name`, e.g. a handler defined by a script
+    /// loaded earlier. Errors are reported like those of [`Lox::eval`].
+    pub fn call(&self, name: &str, arguments: Vec<Literal>) -> Result<Literal, LoxResult> {
+        let result = self.interpreter.call_global(name, arguments);
+        let _ = self.interpreter.output().flush();
+        result.inspect_err(|error| self.interpreter.report(error))
+    }
+
+    /// Defines (or redefines) a global variable.
+    pub fn define(&self, name: &str, value: Literal) {
+        self.interpreter.define_global(name, value);
+    }
+
+    /// Reads a global variable.
+    pub fn get(&self, name: &str) -> Option<Literal> {
+        self.interpreter.global(name)
+    }
+}
+
+impl Default for Lox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet() -> Lox {
+        let mut lox = Lox::new();
+        lox.interpreter_mut()
+            .set_diagnostics(Box::new(SharedBuffer::new()));
+        lox
+    }
+
+    #[test]
+    fn test_eval_returns_last_expression() {
+        let lox = quiet();
+        assert_eq!(lox.eval("1 + 2;").unwrap(), Literal::Number(3.0));
+        assert_eq!(lox.eval("var x = 1;").unwrap(), Literal::Nil);
+        assert_eq!(lox.eval("return 4;").unwrap(), Literal::Number(4.0));
+    }
+
+    #[test]
+    fn test_globals_persist_between_calls() {
+        let lox = quiet();
+        lox.define("base", Literal::Number(40.0));
+        lox.eval("var answer = base + 2;").unwrap();
+        assert_eq!(lox.get("answer"), Some(Literal::Number(42.0)));
+        assert_eq!(lox.eval("answer;").unwrap(), Literal::Number(42.0));
+    }
+
+    #[test]
+    fn test_call_script_function() {
+        let lox = quiet();
+        lox.eval("fun greet(name, times) { return repeat(name, times); }")
+            .unwrap();
+        let greeting = lox.call("greet", vec!["ab".into(), 2.into()]).unwrap();
+        assert_eq!(String::try_from(greeting).unwrap(), "abab");
+        assert_eq!(
+            lox.call("len", vec![vec![1, 2, 3].into()]).unwrap(),
+            Literal::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn test_call_errors() {
+        let lox = quiet();
+        lox.eval("var limit = 1; fun one(x) { return x; }").unwrap();
+        for (name, arguments, message) in [
+            ("missing", vec![], "Undefined variable: missing"),
+            ("limit", vec![], "'limit' is a number, not a function"),
+            ("one", vec![], "Expected 1 arguments, received 0"),
+        ] {
+            assert!(matches!(
+                lox.call(name, arguments),
+                Err(LoxResult::RuntimeError { token, message: m, .. }) if token.lexeme == name && m == message
+            ));
+        }
+    }
+
+    #[test]
+    fn test_interrupt_keeps_session() {
+        let lox = quiet();
+        lox.eval("var count = 0;").unwrap();
+        let interrupt = lox.interrupt_handle();
+        let trigger = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            interrupt.interrupt();
+        });
+        assert!(matches!(
+            lox.eval("while (true) { count = count + 1; }"),
+            Err(LoxResult::RuntimeError { message, .. }) if message == "Interrupted"
+        ));
+        trigger.join().unwrap();
+        assert_eq!(lox.eval("count > 0;").unwrap(), Literal::Boolean(true));
+    }
+
+    #[test]
+    fn test_host_calls_are_labelled_in_call_stacks() {
+        let mut lox = Lox::new();
+        let (_, diagnostics) = lox.capture();
+        lox.eval("fun handler() { return missing; }").unwrap();
+        assert!(lox.call("handler", vec![]).is_err());
+        assert_eq!(
+            diagnostics.take(),
+            "Call stack (innermost last):\n  host: handler()\n\
+             Line: 1 at 'missing' : Undefined variable: missing \n"
+        );
+    }
+
+    #[test]
+    fn test_capture_output_and_diagnostics() {
+        let mut lox = Lox::new();
+        let (output, diagnostics) = lox.capture();
+        let _ = lox.eval("print 1; write(2); print missing;");
+        assert_eq!(output.take(), "1\n2");
+        assert_eq!(
+            diagnostics.take(),
+            "Line: 1 at 'missing' : Undefined variable: missing \n"
+        );
+    }
+
+    #[test]
+    fn test_interrupt_before_evaluation_is_kept() {
+        let lox = quiet();
+        lox.interrupt_handle().interrupt();
+        assert!(matches!(
+            lox.eval("while (true) {}"),
+            Err(LoxResult::RuntimeError { message, .. }) if message == "Interrupted"
+        ));
+        assert!(!lox.interrupt_handle().is_interrupted());
+        assert_eq!(lox.eval("1;").unwrap(), Literal::Number(1.0));
+    }
+
+    #[test]
+    fn test_errors_are_structured() {
+        let lox = quiet();
+        assert!(matches!(
+            lox.eval("print missing;"),
+            Err(LoxResult::RuntimeError { token, .. }) if token.lexeme == "missing"
+        ));
+        assert!(matches!(
+            lox.eval("var = ;"),
+            Err(LoxResult::ParseError { .. })
+        ));
+        assert!(matches!(
+            lox.eval_file("/nonexistent/script.lox"),
+            Err(LoxResult::SystemError { .. })
+        ));
+    }
+}
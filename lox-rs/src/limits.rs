@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+/// Call depth allowed by default. Calls grow the Rust stack on the heap when
+/// it runs low, so the depth is what bounds memory use, not the thread's
+/// stack size.
+pub const DEFAULT_MAX_DEPTH: usize = 1000;
+
+// Evaluating one call, or parsing one nested expression, takes a few dozen
+// deeply nested Rust frames, so a new stack segment is allocated before them
+// once less than this is left.
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+/// Runs `f`, first moving to a fresh stack segment when the current one is
+/// nearly exhausted.
+pub(crate) fn grow_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, f)
+}
+
+/// Bounds on a single evaluation (one [`Interpreter::run`] or
+/// [`Interpreter::call_global`]), for running untrusted scripts. Budgets are
+/// checked on every loop iteration and function call; `None` means
+/// unlimited.
+///
+/// [`Interpreter::run`]: crate::interpreter::Interpreter::run
+/// [`Interpreter::call_global`]: crate::interpreter::Interpreter::call_global
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Number of statements executed.
+    pub max_steps: Option<u64>,
+    /// Wall-clock time.
+    pub max_duration: Option<Duration>,
+    /// Number of nested calls, natives included.
+    pub max_depth: Option<usize>,
+}
+
+impl Limits {
+    pub fn unlimited() -> Limits {
+        Limits {
+            max_steps: None,
+            max_duration: None,
+            max_depth: None,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            ..Limits::unlimited()
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use crate::callable::*;
+use crate::conversion_functions::type_name;
+use crate::error::LoxResult;
+use crate::namespace::Namespace;
+use crate::userdata::UserData;
 use std::fmt::{self};
+use std::rc::Rc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
@@ -9,6 +14,9 @@ pub enum Literal {
     Boolean(bool),
     Nil,
     Func(Callable),
+    List(Rc<Vec<Literal>>),
+    Namespace(Namespace),
+    UserData(UserData),
 }
 
 impl fmt::Display for Literal {
@@ -20,7 +28,174 @@ impl fmt::Display for Literal {
             Literal::Nil => write!(f, "nil"),
             Literal::Identifier(x) => write!(f, "var: {x}"),
             Literal::Func(x) => write!(f, "fn {x}"),
+            Literal::List(x) => {
+                let elements: Vec<String> = x.iter().map(|e| e.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Literal::Namespace(x) => write!(f, "{x}"),
+            Literal::UserData(x) => write!(f, "{x}"),
         };
         Ok(())
     }
 }
+
+impl From<f64> for Literal {
+    fn from(value: f64) -> Self {
+        Literal::Number(value)
+    }
+}
+
+impl From<i32> for Literal {
+    fn from(value: i32) -> Self {
+        Literal::Number(value.into())
+    }
+}
+
+/// Lox numbers are `f64`, so integers beyond 2^53 lose precision.
+impl From<i64> for Literal {
+    fn from(value: i64) -> Self {
+        Literal::Number(value as f64)
+    }
+}
+
+impl From<bool> for Literal {
+    fn from(value: bool) -> Self {
+        Literal::Boolean(value)
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(value: &str) -> Self {
+        Literal::String(value.to_string())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(value: String) -> Self {
+        Literal::String(value)
+    }
+}
+
+impl From<Callable> for Literal {
+    fn from(value: Callable) -> Self {
+        Literal::Func(value)
+    }
+}
+
+impl<T: Into<Literal>> From<Option<T>> for Literal {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Literal::Nil, Into::into)
+    }
+}
+
+impl<T: Into<Literal>> From<Vec<T>> for Literal {
+    fn from(values: Vec<T>) -> Self {
+        Literal::List(Rc::new(values.into_iter().map(Into::into).collect()))
+    }
+}
+
+fn conversion_error(value: &Literal, target: &str) -> LoxResult {
+    LoxResult::system_error(&format!(
+        "cannot convert {} {} to {}",
+        type_name(value),
+        value,
+        target
+    ))
+}
+
+impl TryFrom<Literal> for f64 {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::Number(x) => Ok(x),
+            other => Err(conversion_error(&other, "f64")),
+        }
+    }
+}
+
+/// Accepts integral numbers within the range of `i64` only.
+impl TryFrom<Literal> for i64 {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::Number(x)
+                if x.fract() == 0.0 && x >= i64::MIN as f64 && x < i64::MAX as f64 =>
+            {
+                Ok(x as i64)
+            }
+            other => Err(conversion_error(&other, "i64")),
+        }
+    }
+}
+
+impl TryFrom<Literal> for bool {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::Boolean(x) => Ok(x),
+            other => Err(conversion_error(&other, "bool")),
+        }
+    }
+}
+
+impl TryFrom<Literal> for String {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::String(x) => Ok(x),
+            other => Err(conversion_error(&other, "String")),
+        }
+    }
+}
+
+impl TryFrom<Literal> for Callable {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::Func(x) => Ok(x),
+            other => Err(conversion_error(&other, "Callable")),
+        }
+    }
+}
+
+impl TryFrom<Literal> for Vec<Literal> {
+    type Error = LoxResult;
+
+    fn try_from(value: Literal) -> Result<Self, Self::Error> {
+        match value {
+            Literal::List(x) => Ok(Rc::unwrap_or_clone(x)),
+            other => Err(conversion_error(&other, "Vec<Literal>")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_values_convert_to_literals() {
+        assert_eq!(Literal::from(2), Literal::Number(2.0));
+        assert_eq!(Literal::from("a"), Literal::String("a".to_string()));
+        assert_eq!(Literal::from(None::<bool>), Literal::Nil);
+        assert_eq!(Literal::from(vec![1.5, 2.5]).to_string(), "[1.5, 2.5]");
+    }
+
+    #[test]
+    fn test_literals_convert_to_rust_values() {
+        assert_eq!(f64::try_from(Literal::Number(1.5)).unwrap(), 1.5);
+        assert_eq!(i64::try_from(Literal::Number(-3.0)).unwrap(), -3);
+        assert!(bool::try_from(Literal::Boolean(true)).unwrap());
+        assert_eq!(String::try_from(Literal::from("x")).unwrap(), "x");
+        assert!(matches!(
+            i64::try_from(Literal::Number(1.5)),
+            Err(LoxResult::SystemError { message }) if message == "cannot convert number 1.5 to i64"
+        ));
+        assert!(String::try_from(Literal::Nil).is_err());
+    }
+}
@@ -1,95 +1,125 @@
-// mod ast_print;
-mod error;
-mod parser;
-mod scanner;
-mod token;
-mod token_type;
-use error::LoxResult;
-use scanner::Scanner;
+use lox_rs::{FsAccess, Limits, Literal, Lox, LoxResult};
 use std::env::args;
-use std::io::{self, stdout, BufRead, Write};
-
-use crate::interpreter::Interpreter;
-mod callable;
-mod environment;
-mod expr;
-mod interpreter;
-mod literal;
-mod lox_function;
-mod native_functions;
-mod stmt;
+use std::io::{self, stdout, Write};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 fn main() {
-    let args: Vec<String> = args().collect();
-    let lox = Lox::new();
-    match args.len() {
-        1 => lox.run_prompt(),
-        2 => lox.run_file(&args[1]),
-        _ => {
-            println!("Usage: lox-rs [script]");
-            std::process::exit(64);
+    let mut lox = Lox::new();
+    lox.interpreter_mut().set_process_access(true);
+    let mut script = None;
+    let mut fs_access = FsAccess::Denied;
+    let mut limits = Limits::default();
+    let mut arguments = args().skip(1);
+    for arg in arguments.by_ref() {
+        match arg.as_str() {
+            "--no-assert" => lox.interpreter_mut().set_assertions(false),
+            "--allow-fs" => fs_access = fs_access.merge(FsAccess::Unrestricted),
+            _ if arg.starts_with("--allow-fs=") => {
+                match FsAccess::scoped(arg.trim_start_matches("--allow-fs=")) {
+                    Ok(scope) => fs_access = fs_access.merge(scope),
+                    Err(e) => {
+                        let _ = e.report(&mut io::stderr());
+                        std::process::exit(64)
+                    }
+                }
+            }
+            _ if arg.starts_with("--seed=") => lox.interpreter().seed(number(&arg)),
+            _ if arg.starts_with("--max-steps=") => limits.max_steps = Some(number(&arg)),
+            _ if arg.starts_with("--timeout=") => {
+                limits.max_duration = Some(Duration::from_millis(number(&arg)))
+            }
+            _ if arg.starts_with("--max-depth=") => limits.max_depth = Some(number(&arg)),
+            _ if !arg.starts_with("--") => {
+                script = Some(arg);
+                break;
+            }
+            _ => usage(),
         }
     }
+    // Everything after the script path belongs to the script.
+    let argv = arguments.map(Literal::String).collect();
+    lox.define("argv", Literal::List(Rc::new(argv)));
+    lox.interpreter_mut().set_fs_access(fs_access);
+    lox.interpreter_mut().set_limits(limits);
+    match script {
+        Some(path) => run_file(&lox, &path),
+        None => run_prompt(&lox),
+    }
 }
 
-struct Lox {
-    interpreter: Interpreter,
+fn usage() -> ! {
+    println!(
+        "Usage: lox-rs [--no-assert] [--allow-fs[=DIR]] [--seed=N] [--max-steps=N] \
+         [--timeout=MS] [--max-depth=N] [script [args...]]"
+    );
+    std::process::exit(64);
 }
 
-impl Lox {
-    pub fn new() -> Lox {
-        Lox {
-            interpreter: Interpreter::new(),
-        }
+/// Value of a `--flag=N` option.
+fn number<T: std::str::FromStr>(arg: &str) -> T {
+    match arg.split_once('=').map(|(_, value)| value.parse()) {
+        Some(Ok(value)) => value,
+        _ => usage(),
     }
-    pub fn run_file(&self, path: &str) {
-        let contents = match std::fs::read_to_string(path) {
-            Ok(contents) => contents,
-            Err(e) => {
-                println!("Error reading file: {}", e);
-                std::process::exit(64);
-            }
-        };
+}
 
-        match self.run(contents) {
-            Ok(_) => (),
-            Err(_e) => {
-                std::process::exit(65);
-            }
+fn run_file(lox: &Lox, path: &str) {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            println!("Error reading file: {}", e);
+            std::process::exit(64);
         }
-    }
-    pub fn run_prompt(&self) {
-        print!("> ");
-        let _ = stdout().flush();
+    };
 
-        let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                if line.is_empty() {
-                    continue;
-                }
-                match self.run(line) {
-                    Ok(_) => (),
-                    Err(_e) => {
-                        // error was already reported in scanner
-                    }
-                }
-            } else {
-                break;
-            }
-            print!("> ");
-            let _ = stdout().flush();
+    match lox.eval(&contents) {
+        Ok(_) => (),
+        Err(LoxResult::Exit { code }) => std::process::exit(code),
+        Err(_e) => {
+            std::process::exit(65);
         }
     }
-    fn run(&self, source: String) -> Result<(), LoxResult> {
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens()?;
-        let mut parser = parser::Parser::new(tokens);
-        let statements = parser.parse()?;
+}
 
-        if parser.success() {
-            self.interpreter.interprete(&statements);
+fn run_prompt(lox: &Lox) {
+    // Ctrl-C aborts the evaluation in progress but keeps the session; at the
+    // prompt, or pressed again before the evaluation stops, it quits as usual.
+    let interrupt = lox.interrupt_handle();
+    let running = Arc::new(AtomicBool::new(false));
+    let evaluating = Arc::clone(&running);
+    let _ = ctrlc::set_handler(move || {
+        if !evaluating.load(Ordering::SeqCst) || interrupt.is_interrupted() {
+            std::process::exit(130);
         }
-        Ok(())
+        interrupt.interrupt();
+    });
+
+    print!("> ");
+    let _ = stdout().flush();
+
+    // The lock on stdin is released after every line, because `readLine` and
+    // `input` read from it too.
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => (),
+        }
+        let line = line.trim_end_matches(['\n', '\r']);
+        if !line.is_empty() {
+            running.store(true, Ordering::SeqCst);
+            let result = lox.eval(line);
+            running.store(false, Ordering::SeqCst);
+            // errors were already reported by the interpreter
+            if let Err(LoxResult::Exit { code }) = result {
+                std::process::exit(code);
+            }
+        }
+        print!("> ");
+        let _ = stdout().flush();
     }
 }
@@ -0,0 +1,230 @@
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::namespace::Namespace;
+use crate::native_functions::{NativeFunction, ParamType};
+
+pub fn register(interpreter: &Interpreter) {
+    let math = interpreter.namespace("math");
+
+    math.define("PI", Literal::Number(std::f64::consts::PI));
+    math.define("E", Literal::Number(std::f64::consts::E));
+    math.define("INF", Literal::Number(f64::INFINITY));
+    math.define("NAN", Literal::Number(f64::NAN));
+
+    unary(&math, "abs", f64::abs);
+    unary(&math, "floor", f64::floor);
+    unary(&math, "ceil", f64::ceil);
+    unary(&math, "round", f64::round);
+    unary(&math, "sin", f64::sin);
+    unary(&math, "cos", f64::cos);
+    unary(&math, "tan", f64::tan);
+    unary(&math, "atan", f64::atan);
+    unary(&math, "exp", f64::exp);
+
+    checked(
+        &math,
+        "sqrt",
+        f64::sqrt,
+        |x| x >= 0.0,
+        "argument must not be negative",
+    );
+    checked(
+        &math,
+        "asin",
+        f64::asin,
+        in_unit_range,
+        "argument must be between -1 and 1",
+    );
+    checked(
+        &math,
+        "acos",
+        f64::acos,
+        in_unit_range,
+        "argument must be between -1 and 1",
+    );
+    checked(
+        &math,
+        "log10",
+        f64::log10,
+        |x| x > 0.0,
+        "argument must be positive",
+    );
+    checked(
+        &math,
+        "log2",
+        f64::log2,
+        |x| x > 0.0,
+        "argument must be positive",
+    );
+
+    math.register_native(
+        NativeFunction::new("log", |_, args| {
+            let x = args.number(0)?;
+            if x <= 0.0 {
+                return Err(args.error("argument must be positive"));
+            }
+            match args.optional_number(1)? {
+                Some(base) if base <= 0.0 || base == 1.0 => {
+                    Err(args.error("base must be positive and not 1"))
+                }
+                Some(base) => Ok(Literal::Number(x.log(base))),
+                None => Ok(Literal::Number(x.ln())),
+            }
+        })
+        .param("x", ParamType::Number)
+        .optional("base", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("pow", |_, args| {
+            let base = args.number(0)?;
+            let exponent = args.number(1)?;
+            let result = base.powf(exponent);
+            if result.is_nan() && !base.is_nan() && !exponent.is_nan() {
+                return Err(args.error("negative base requires an integral exponent"));
+            }
+            Ok(Literal::Number(result))
+        })
+        .param("base", ParamType::Number)
+        .param("exponent", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("atan2", |_, args| {
+            Ok(Literal::Number(args.number(0)?.atan2(args.number(1)?)))
+        })
+        .param("y", ParamType::Number)
+        .param("x", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("min", |_, args| {
+            fold(args.len(), |i| args.number(i), f64::min)
+        })
+        .param("first", ParamType::Number)
+        .variadic("others", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("max", |_, args| {
+            fold(args.len(), |i| args.number(i), f64::max)
+        })
+        .param("first", ParamType::Number)
+        .variadic("others", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("isNan", |_, args| {
+            Ok(Literal::Boolean(args.number(0)?.is_nan()))
+        })
+        .param("x", ParamType::Number),
+    );
+
+    math.register_native(
+        NativeFunction::new("isFinite", |_, args| {
+            Ok(Literal::Boolean(args.number(0)?.is_finite()))
+        })
+        .param("x", ParamType::Number),
+    );
+}
+
+fn in_unit_range(x: f64) -> bool {
+    (-1.0..=1.0).contains(&x)
+}
+
+fn unary(math: &Namespace, name: &str, function: fn(f64) -> f64) {
+    math.register_native(
+        NativeFunction::new(name, move |_, args| {
+            Ok(Literal::Number(function(args.number(0)?)))
+        })
+        .param("x", ParamType::Number),
+    );
+}
+
+/// Registers a one-argument function which reports a domain error instead
+/// of returning `NaN` for arguments outside of `valid`.
+fn checked(
+    math: &Namespace,
+    name: &str,
+    function: fn(f64) -> f64,
+    valid: fn(f64) -> bool,
+    message: &'static str,
+) {
+    math.register_native(
+        NativeFunction::new(name, move |_, args| {
+            let x = args.number(0)?;
+            if x.is_nan() || valid(x) {
+                Ok(Literal::Number(function(x)))
+            } else {
+                Err(args.error(message))
+            }
+        })
+        .param("x", ParamType::Number),
+    );
+}
+
+fn fold<E>(
+    count: usize,
+    number: impl Fn(usize) -> Result<f64, E>,
+    pick: fn(f64, f64) -> f64,
+) -> Result<Literal, E> {
+    let mut result = number(0)?;
+    for index in 1..count {
+        result = pick(result, number(index)?);
+    }
+    Ok(Literal::Number(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native;
+
+    #[test]
+    fn test_min_max_are_variadic() {
+        assert_eq!(
+            call_native(Some("math"), "min", [3.0, 1.0, 2.0]).unwrap(),
+            Literal::Number(1.0)
+        );
+        assert_eq!(
+            call_native(Some("math"), "max", [3.0, 1.0, 2.0]).unwrap(),
+            Literal::Number(3.0)
+        );
+        assert_eq!(
+            call_native(Some("math"), "max", [-1.0]).unwrap(),
+            Literal::Number(-1.0)
+        );
+    }
+
+    #[test]
+    fn test_log_with_base() {
+        assert_eq!(
+            call_native(Some("math"), "log", [8.0, 2.0]).unwrap(),
+            Literal::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn test_domain_errors() {
+        assert!(call_native(Some("math"), "sqrt", [-1.0]).is_err());
+        assert!(call_native(Some("math"), "log", [0.0]).is_err());
+        assert!(call_native(Some("math"), "acos", [2.0]).is_err());
+        assert!(call_native(Some("math"), "pow", [-8.0, 1.0 / 3.0]).is_err());
+        assert_eq!(
+            call_native(Some("math"), "pow", [-2.0, 3.0]).unwrap(),
+            Literal::Number(-8.0)
+        );
+    }
+
+    #[test]
+    fn test_nan_checks() {
+        assert_eq!(
+            call_native(Some("math"), "isNan", [f64::NAN]).unwrap(),
+            Literal::Boolean(true)
+        );
+        assert_eq!(
+            call_native(Some("math"), "isFinite", [f64::INFINITY]).unwrap(),
+            Literal::Boolean(false)
+        );
+    }
+}
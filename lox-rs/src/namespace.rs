@@ -0,0 +1,86 @@
+use crate::callable::*;
+use crate::literal::*;
+use crate::native_functions::NativeFunction;
+use core::fmt::{Debug, Display};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A named group of values, reachable from Lox with `name.member`.
+#[derive(Clone)]
+pub struct Namespace {
+    name: Rc<String>,
+    members: Rc<RefCell<HashMap<String, Literal>>>,
+}
+
+impl Namespace {
+    pub fn new(name: &str) -> Namespace {
+        Namespace {
+            name: Rc::new(name.to_string()),
+            members: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn define(&self, name: &str, value: Literal) {
+        self.members.borrow_mut().insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Literal> {
+        self.members.borrow().get(name).cloned()
+    }
+
+    /// All members, sorted by name.
+    pub fn members(&self) -> Vec<(String, Literal)> {
+        let mut members: Vec<(String, Literal)> = self
+            .members
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        members
+    }
+
+    pub fn register_native(&self, native: NativeFunction) {
+        let name = native.name().to_string();
+        self.define(
+            &name,
+            Literal::Func(Callable {
+                func: Rc::new(native),
+            }),
+        );
+    }
+
+    /// Returns the nested namespace with the given name, creating it first
+    /// if needed.
+    pub fn namespace(&self, name: &str) -> Namespace {
+        if let Some(Literal::Namespace(existing)) = self.get(name) {
+            return existing;
+        }
+        let namespace = Namespace::new(name);
+        self.define(name, Literal::Namespace(namespace.clone()));
+        namespace
+    }
+}
+
+impl Debug for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "namespace {}", self.name)
+    }
+}
+
+impl Display for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "namespace {}", self.name)
+    }
+}
+
+impl PartialEq for Namespace {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.members, &other.members)
+    }
+}
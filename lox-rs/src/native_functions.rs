@@ -2,28 +2,391 @@ use crate::callable::*;
 use crate::error::LoxResult;
 use crate::interpreter::*;
 use crate::literal::Literal;
+use crate::token::Token;
+use crate::userdata::UserData;
+use std::rc::Rc;
 use std::time::SystemTime;
-pub struct LoxClock {}
 
-impl LoxCallable for LoxClock {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamType {
+    Any,
+    Number,
+    String,
+    Bool,
+    Function,
+    List,
+    UserData,
+}
+
+impl ParamType {
+    fn accepts(&self, value: &Literal) -> bool {
+        matches!(
+            (self, value),
+            (ParamType::Any, _)
+                | (ParamType::Number, Literal::Number(_))
+                | (ParamType::String, Literal::String(_))
+                | (ParamType::Bool, Literal::Boolean(_))
+                | (ParamType::Function, Literal::Func(_))
+                | (ParamType::List, Literal::List(_))
+                | (ParamType::UserData, Literal::UserData(_))
+        )
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            ParamType::Any => "a value",
+            ParamType::Number => "a number",
+            ParamType::String => "a string",
+            ParamType::Bool => "a boolean",
+            ParamType::Function => "a function",
+            ParamType::List => "a list",
+            ParamType::UserData => "a host object",
+        }
+    }
+}
+
+struct Param {
+    name: String,
+    kind: ParamType,
+}
+
+type NativeBody = dyn Fn(&Interpreter, &NativeArgs) -> Result<Literal, LoxResult>;
+
+/// A Rust closure callable from Lox. Parameters are declared with the
+/// builder methods, in order: required ones, then optional ones, then at
+/// most one variadic parameter collecting the remaining arguments.
+pub struct NativeFunction {
+    name: String,
+    required: Vec<Param>,
+    optional: Vec<Param>,
+    variadic: Option<Param>,
+    body: Box<NativeBody>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, body: F) -> NativeFunction
+    where
+        F: Fn(&Interpreter, &NativeArgs) -> Result<Literal, LoxResult> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            required: Vec::new(),
+            optional: Vec::new(),
+            variadic: None,
+            body: Box::new(body),
+        }
+    }
+
+    pub fn param(mut self, name: &str, kind: ParamType) -> Self {
+        self.required.push(Param {
+            name: name.to_string(),
+            kind,
+        });
+        self
+    }
+
+    pub fn optional(mut self, name: &str, kind: ParamType) -> Self {
+        self.optional.push(Param {
+            name: name.to_string(),
+            kind,
+        });
+        self
+    }
+
+    pub fn variadic(mut self, name: &str, kind: ParamType) -> Self {
+        self.variadic = Some(Param {
+            name: name.to_string(),
+            kind,
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_optional(&self, index: usize) -> bool {
+        (self.required.len()..self.required.len() + self.optional.len()).contains(&index)
+    }
+
+    fn param_at(&self, index: usize) -> Option<&Param> {
+        self.required
+            .iter()
+            .chain(self.optional.iter())
+            .nth(index)
+            .or(self.variadic.as_ref())
+    }
+}
+
+impl LoxCallable for NativeFunction {
     fn call(
         &self,
-        _interpreter: &Interpreter,
-        _arguments: Vec<Literal>,
+        interpreter: &Interpreter,
+        arguments: Vec<Literal>,
     ) -> Result<Literal, LoxResult> {
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => Ok(Literal::Number(n.as_millis() as f64)),
-            Err(e) => Err(LoxResult::system_error(&format!(
-                "System clock returned invalid value: {:?} ",
-                e
-            ))),
+        let args = NativeArgs {
+            native: self,
+            site: interpreter.call_site(),
+            values: arguments,
+        };
+        for (index, value) in args.values.iter().enumerate() {
+            args.expect(index, value)?;
         }
+        (self.body)(interpreter, &args)
     }
 
     fn arity(&self) -> usize {
-        0
+        self.required.len()
     }
+
+    fn max_arity(&self) -> Option<usize> {
+        match self.variadic {
+            Some(_) => None,
+            None => Some(self.required.len() + self.optional.len()),
+        }
+    }
+
     fn to_string(&self) -> String {
-        "Native::Clock".to_string()
+        format!("Native::{}", self.name)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// Arguments of a native call, already checked against the declared
+/// parameter types. Missing optional arguments read as `nil`, and passing
+/// `nil` explicitly is the same as leaving them out.
+pub struct NativeArgs<'a> {
+    native: &'a NativeFunction,
+    site: Token,
+    values: Vec<Literal>,
+}
+
+impl NativeArgs<'_> {
+    pub fn name(&self) -> &str {
+        &self.native.name
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Literal {
+        self.values.get(index).cloned().unwrap_or(Literal::Nil)
+    }
+
+    pub fn rest(&self, from: usize) -> &[Literal] {
+        self.values.get(from..).unwrap_or(&[])
+    }
+
+    pub fn number(&self, index: usize) -> Result<f64, LoxResult> {
+        match self.get(index) {
+            Literal::Number(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::Number, &other)),
+        }
+    }
+
+    pub fn string(&self, index: usize) -> Result<String, LoxResult> {
+        match self.get(index) {
+            Literal::String(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::String, &other)),
+        }
+    }
+
+    pub fn boolean(&self, index: usize) -> Result<bool, LoxResult> {
+        match self.get(index) {
+            Literal::Boolean(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::Bool, &other)),
+        }
+    }
+
+    pub fn function(&self, index: usize) -> Result<Callable, LoxResult> {
+        match self.get(index) {
+            Literal::Func(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::Function, &other)),
+        }
+    }
+
+    pub fn list(&self, index: usize) -> Result<Rc<Vec<Literal>>, LoxResult> {
+        match self.get(index) {
+            Literal::List(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::List, &other)),
+        }
+    }
+
+    pub fn userdata(&self, index: usize) -> Result<UserData, LoxResult> {
+        match self.get(index) {
+            Literal::UserData(x) => Ok(x),
+            other => Err(self.mismatch(index, ParamType::UserData, &other)),
+        }
+    }
+
+    /// Number argument which must also be integral.
+    pub fn integer(&self, index: usize) -> Result<i64, LoxResult> {
+        let x = self.number(index)?;
+        i64::try_from(Literal::Number(x)).map_err(|_| {
+            self.error(&format!(
+                "parameter '{}' expects an integer, received {}",
+                self.param_name(index),
+                x
+            ))
+        })
+    }
+
+    pub fn optional_number(&self, index: usize) -> Result<Option<f64>, LoxResult> {
+        match self.get(index) {
+            Literal::Nil => Ok(None),
+            _ => self.number(index).map(Some),
+        }
+    }
+
+    pub fn optional_string(&self, index: usize) -> Result<Option<String>, LoxResult> {
+        match self.get(index) {
+            Literal::Nil => Ok(None),
+            _ => self.string(index).map(Some),
+        }
+    }
+
+    /// Calls a function received as an argument, reporting a callback which
+    /// does not accept `arguments` by its name.
+    pub fn call(
+        &self,
+        interpreter: &Interpreter,
+        function: &Callable,
+        arguments: Vec<Literal>,
+    ) -> Result<Literal, LoxResult> {
+        if let Some(expected) = arity_mismatch(function, arguments.len()) {
+            return Err(self.error(&format!(
+                "callback '{}' expects {} arguments, received {}",
+                function,
+                expected,
+                arguments.len()
+            )));
+        }
+        interpreter.call_function(function, arguments, &self.site)
+    }
+
+    /// Runtime error at the call site, prefixed with the native's name.
+    pub fn error(&self, message: &str) -> LoxResult {
+        LoxResult::interp_error(&self.site, &format!("{}(): {}", self.native.name, message))
+    }
+
+    fn expect(&self, index: usize, value: &Literal) -> Result<(), LoxResult> {
+        if *value == Literal::Nil && self.native.is_optional(index) {
+            return Ok(());
+        }
+        match self.native.param_at(index) {
+            Some(param) if !param.kind.accepts(value) => {
+                Err(self.mismatch(index, param.kind, value))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn mismatch(&self, index: usize, kind: ParamType, value: &Literal) -> LoxResult {
+        self.error(&format!(
+            "parameter '{}' expects {}, received {}",
+            self.param_name(index),
+            kind.describe(),
+            value
+        ))
+    }
+
+    fn param_name(&self, index: usize) -> String {
+        self.native
+            .param_at(index)
+            .map_or_else(|| format!("#{}", index + 1), |param| param.name.clone())
+    }
+}
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(NativeFunction::new("clock", |_, _| match SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+    {
+        Ok(n) => Ok(Literal::Number(n.as_millis() as f64)),
+        Err(e) => Err(LoxResult::system_error(&format!(
+            "System clock returned invalid value: {:?} ",
+            e
+        ))),
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(native: NativeFunction, arguments: Vec<Literal>) -> Result<Literal, LoxResult> {
+        native.call(&Interpreter::new(), arguments)
+    }
+
+    fn sum() -> NativeFunction {
+        NativeFunction::new("sum", |_, args| {
+            let mut total = args.number(0)?;
+            for value in args.rest(1) {
+                if let Literal::Number(x) = value {
+                    total += x;
+                }
+            }
+            Ok(Literal::Number(total))
+        })
+        .param("first", ParamType::Number)
+        .variadic("others", ParamType::Number)
+    }
+
+    #[test]
+    fn test_variadic_arguments_are_collected() {
+        let arguments = vec![1.0, 2.0, 3.0]
+            .into_iter()
+            .map(Literal::Number)
+            .collect();
+        assert_eq!(call(sum(), arguments).unwrap(), Literal::Number(6.0));
+    }
+
+    #[test]
+    fn test_arity_range() {
+        let native = NativeFunction::new("pad", |_, args| Ok(args.get(1)))
+            .param("text", ParamType::String)
+            .optional("width", ParamType::Number);
+        assert_eq!(native.arity(), 1);
+        assert_eq!(native.max_arity(), Some(2));
+        assert_eq!(sum().max_arity(), None);
+        assert_eq!(
+            call(native, vec![Literal::String("x".to_string())]).unwrap(),
+            Literal::Nil
+        );
+    }
+
+    #[test]
+    fn test_explicit_nil_for_optional_parameter() {
+        let native = || {
+            NativeFunction::new("pad", |_, args| {
+                Ok(Literal::Number(args.optional_number(1)?.unwrap_or(0.0)))
+            })
+            .param("text", ParamType::String)
+            .optional("width", ParamType::Number)
+        };
+        let text = Literal::String("x".to_string());
+        assert_eq!(
+            call(native(), vec![text.clone(), Literal::Nil]).unwrap(),
+            Literal::Number(0.0)
+        );
+        assert!(call(native(), vec![Literal::Nil]).is_err());
+    }
+
+    #[test]
+    fn test_type_mismatch_names_parameter() {
+        let arguments = vec![Literal::Number(1.0), Literal::String("2".to_string())];
+        let result = call(sum(), arguments);
+        assert!(matches!(
+            result,
+            Err(LoxResult::RuntimeError { message, .. })
+                if message == "sum(): parameter 'others' expects a number, received 2"
+        ));
     }
 }
@@ -0,0 +1,38 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// In-memory sink which can be handed to the interpreter while a clone is
+/// kept to read back what was written.
+#[derive(Clone, Default)]
+pub struct SharedBuffer {
+    bytes: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn new() -> SharedBuffer {
+        SharedBuffer::default()
+    }
+
+    /// Everything written so far, with invalid UTF-8 replaced.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.bytes.borrow()).into_owned()
+    }
+
+    /// Returns the contents and empties the buffer.
+    pub fn take(&self) -> String {
+        let bytes = self.bytes.take();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
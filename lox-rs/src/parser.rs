@@ -4,33 +4,53 @@ use crate::literal::Literal;
 
 use crate::error::LoxResult;
 use crate::expr::*;
+use crate::limits;
 use crate::stmt::*;
 use crate::token::*;
 use crate::token_type::*;
+use crate::typecheck::Type;
+
+/// Deepest nesting of statements and expressions the parser accepts, so
+/// that checking and running the program cannot overflow the stack.
+const MAX_NESTING: usize = 255;
 
 pub struct Parser<'a> {
     pub tokens: &'a [Token],
     current: usize,
-    had_error: bool,
+    depth: usize,
+    errors: Vec<LoxResult>,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &[Token]) -> Parser {
+    pub fn new(tokens: &[Token]) -> Parser<'_> {
         Parser {
             tokens,
             current: 0,
-            had_error: false,
+            depth: 0,
+            errors: Vec::new(),
         }
     }
     pub fn success(&self) -> bool {
-        !self.had_error
+        self.errors.is_empty()
     }
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, LoxResult> {
+    /// Parses the whole program, returning every error found (including ones
+    /// the parser could recover from) when it is not valid.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<LoxResult>> {
         let mut statements = Vec::new();
         while !self.is_at_end() {
-            statements.push(self.declaration()?)
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    self.errors.push(e);
+                    break;
+                }
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(std::mem::take(&mut self.errors))
         }
-        Ok(statements)
     }
 
     fn declaration(&mut self) -> Result<Stmt, LoxResult> {
@@ -54,20 +74,25 @@ impl<'a> Parser<'a> {
             &format!("Expect '(' after {kind} name"),
         )?;
         let mut params = Vec::new();
+        let mut param_types = Vec::new();
         if !self.check(TokenType::RightParen) {
             params.push(self.consume(TokenType::Identifier, "Expected parameter name")?);
+            param_types.push(self.type_annotation()?);
             while self.is_match(&[TokenType::Comma]) {
-                if params.len() > 255 && !self.had_error {
+                if params.len() > 255 && self.success() {
                     let look = self.peek().clone();
-                    self.error(&look, "Function cannot have more than 255 parameters");
+                    let error = self.error(&look, "Function cannot have more than 255 parameters");
+                    self.errors.push(error);
                 }
                 params.push(self.consume(TokenType::Identifier, "Expected parameter name")?);
+                param_types.push(self.type_annotation()?);
             }
         }
         self.consume(
             TokenType::RightParen,
             "Expect ')' after function declaration",
         )?;
+        let return_type = self.type_annotation()?;
 
         self.consume(
             TokenType::LeftBrace,
@@ -77,12 +102,32 @@ impl<'a> Parser<'a> {
         Ok(Stmt::Function(StmtFunction {
             name,
             params: Rc::new(params),
+            param_types: Rc::new(param_types),
+            return_type,
             body,
         }))
     }
 
+    fn type_annotation(&mut self) -> Result<Option<Token>, LoxResult> {
+        if !self.is_match(&[TokenType::Colon]) {
+            return Ok(None);
+        }
+        let annotation = self.consume(TokenType::Identifier, "Expected type name after ':'")?;
+        if Type::from_annotation(&annotation).is_none() {
+            return Err(self.error(
+                &annotation,
+                &format!("Unknown type '{}'", annotation.as_string()),
+            ));
+        }
+        Ok(Some(annotation))
+    }
+
     fn var_declaration(&mut self) -> Result<Stmt, LoxResult> {
+        if self.is_match(&[TokenType::LeftBracket]) {
+            return self.destructure_declaration();
+        }
         let name = self.consume(TokenType::Identifier, "Expected variable name")?;
+        let type_annotation = self.type_annotation()?;
         let initializer = if self.is_match(&[TokenType::Assign]) {
             Some(self.expression()?)
         } else {
@@ -94,10 +139,45 @@ impl<'a> Parser<'a> {
             "Expect ';' after variable declaration",
         )?;
 
-        Ok(Stmt::Var(StmtVar { name, initializer }))
+        Ok(Stmt::Var(StmtVar {
+            name,
+            type_annotation,
+            initializer,
+        }))
+    }
+
+    fn destructure_declaration(&mut self) -> Result<Stmt, LoxResult> {
+        let bracket = self.previous().clone();
+        let mut names = Vec::new();
+        if !self.check(TokenType::RightBracket) {
+            names.push(self.consume(TokenType::Identifier, "Expected variable name")?);
+            while self.is_match(&[TokenType::Comma]) {
+                names.push(self.consume(TokenType::Identifier, "Expected variable name")?);
+            }
+        }
+        self.consume(
+            TokenType::RightBracket,
+            "Expect ']' after destructuring targets",
+        )?;
+        self.consume(TokenType::Assign, "Expect '=' after destructuring targets")?;
+        let initializer = self.expression()?;
+        self.consume(
+            TokenType::Semicolon,
+            "Expect ';' after variable declaration",
+        )?;
+
+        Ok(Stmt::Destructure(StmtDestructure {
+            bracket,
+            names,
+            initializer,
+        }))
     }
 
     fn statement(&mut self) -> Result<Stmt, LoxResult> {
+        self.nested(Self::nested_statement)
+    }
+
+    fn nested_statement(&mut self) -> Result<Stmt, LoxResult> {
         if self.is_match(&[TokenType::If]) {
             return self.if_statement();
         }
@@ -123,6 +203,9 @@ impl<'a> Parser<'a> {
         if self.is_match(&[TokenType::Print]) {
             return self.print_statement();
         }
+        if self.is_match(&[TokenType::Assert]) {
+            return self.assert_statement();
+        }
         if self.is_match(&[TokenType::LeftBrace]) {
             return Ok(Stmt::Block(StmtBlock {
                 statements: self.block()?,
@@ -142,6 +225,7 @@ impl<'a> Parser<'a> {
     }
 
     fn for_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LeftParen, "Expected '(' after 'for' keyword")?;
         let initializer = if self.is_match(&[TokenType::Semicolon]) {
             None
@@ -175,6 +259,7 @@ impl<'a> Parser<'a> {
         }
 
         body = Stmt::While(StmtWhile {
+            keyword,
             condition: if let Some(cond) = condition {
                 cond
             } else {
@@ -194,6 +279,7 @@ impl<'a> Parser<'a> {
         Ok(body)
     }
     fn while_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().clone();
         self.consume(TokenType::LeftParen, "Expected '(' after 'while' statement")?;
         let condition = self.expression()?;
         self.consume(
@@ -204,6 +290,7 @@ impl<'a> Parser<'a> {
         let body = self.statement()?;
 
         Ok(Stmt::While(StmtWhile {
+            keyword,
             condition,
             body: Box::new(body),
         }))
@@ -214,6 +301,52 @@ impl<'a> Parser<'a> {
         self.consume(TokenType::Semicolon, "Expected ';' after the statement!")?;
         Ok(Stmt::Print(StmtPrint { expression: value }))
     }
+    fn assert_statement(&mut self) -> Result<Stmt, LoxResult> {
+        let keyword = self.previous().clone();
+        let start = self.current;
+        let condition = self.expression()?;
+        let source = Parser::source_text(&self.tokens[start..self.current]);
+        let message = if self.is_match(&[TokenType::Comma]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "Expected ';' after the assertion")?;
+
+        Ok(Stmt::Assert(StmtAssert {
+            keyword,
+            condition,
+            message,
+            source,
+        }))
+    }
+
+    fn source_text(tokens: &[Token]) -> String {
+        let mut text = String::new();
+        let mut previous: Option<&Token> = None;
+        for token in tokens {
+            if let Some(previous) = previous {
+                let glued = matches!(
+                    token.token_type(),
+                    TokenType::RightParen | TokenType::Comma | TokenType::Dot
+                ) || matches!(
+                    previous.token_type(),
+                    TokenType::LeftParen | TokenType::Bang | TokenType::Dot
+                ) || (token.is(TokenType::LeftParen)
+                    && matches!(
+                        previous.token_type(),
+                        TokenType::Identifier | TokenType::RightParen
+                    ));
+                if !glued {
+                    text.push(' ');
+                }
+            }
+            text.push_str(&token.lexeme);
+            previous = Some(token);
+        }
+        text
+    }
+
     fn return_statement(&mut self) -> Result<Stmt, LoxResult> {
         let keyword = self.previous().clone();
         let value = if self.check(TokenType::Semicolon) {
@@ -253,7 +386,7 @@ impl<'a> Parser<'a> {
     }
 
     pub fn expression(&mut self) -> Result<Expr, LoxResult> {
-        self.assignment()
+        self.nested(Self::assignment)
     }
 
     fn assignment(&mut self) -> Result<Expr, LoxResult> {
@@ -261,7 +394,7 @@ impl<'a> Parser<'a> {
 
         if self.is_match(&[TokenType::Assign]) {
             let equals = self.previous().clone();
-            let value = self.assignment()?;
+            let value = self.nested(Self::assignment)?;
 
             if let Expr::Variable(expr) = expr {
                 return Ok(Expr::Assign(ExprAssign {
@@ -269,16 +402,34 @@ impl<'a> Parser<'a> {
                     value: Box::new(value),
                 }));
             }
-            self.error(&equals, "Invalid l-value for assignment");
+            if let Expr::List(list) = &expr {
+                let mut names = Vec::new();
+                for element in &list.elements {
+                    if let Expr::Variable(target) = element {
+                        names.push(target.name.clone());
+                    } else {
+                        return Err(self.error(&equals, "Invalid destructuring target"));
+                    }
+                }
+                return Ok(Expr::Unpack(ExprUnpack {
+                    bracket: list.bracket.clone(),
+                    names,
+                    value: Box::new(value),
+                }));
+            }
+            let error = self.error(&equals, "Invalid l-value for assignment");
+            self.errors.push(error);
         }
         Ok(expr)
     }
 
     fn or(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.and()?;
 
         while self.is_match(&[TokenType::Or]) {
             let operator = self.previous().clone();
+            self.deepen()?;
             let right = Box::new(self.and()?);
             expr = Expr::Logical(ExprLogical {
                 left: Box::new(expr),
@@ -286,14 +437,17 @@ impl<'a> Parser<'a> {
                 right,
             })
         }
+        self.depth = depth;
         Ok(expr)
     }
 
     fn and(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.equality()?;
 
         while self.is_match(&[TokenType::And]) {
             let operator = self.previous().clone();
+            self.deepen()?;
             let right = Box::new(self.equality()?);
             expr = Expr::Logical(ExprLogical {
                 left: Box::new(expr),
@@ -302,26 +456,109 @@ impl<'a> Parser<'a> {
             });
         }
 
+        self.depth = depth;
         Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expr, LoxResult> {
-        let mut expr = self.comparison()?;
+        let depth = self.depth;
+        let mut expr = self.pipeline()?;
 
         while self.is_match(&[TokenType::BangEqual, TokenType::Equals]) {
             let operator = self.previous().clone();
-            let right = self.comparison()?;
+            self.deepen()?;
+            let right = self.pipeline()?;
             expr = Expr::Binary(ExprBinary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
             });
         }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn pipeline(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
+        let mut expr = self.bit_or()?;
+
+        while self.is_match(&[TokenType::Pipe]) {
+            let operator = self.previous().clone();
+            self.deepen()?;
+            let right = self.bit_or()?;
+            expr = match right {
+                Expr::Call(call) => {
+                    let mut arguments = vec![expr];
+                    arguments.extend(call.arguments);
+                    Expr::Call(ExprCall {
+                        callee: call.callee,
+                        paren: call.paren,
+                        arguments,
+                    })
+                }
+                callee => Expr::Call(ExprCall {
+                    callee: Rc::new(callee),
+                    paren: operator,
+                    arguments: vec![expr],
+                }),
+            };
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+
+    fn bit_or(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
+        let mut expr = self.bit_xor()?;
+        while self.is_match(&[TokenType::Bar]) {
+            let operator = self.previous().clone();
+            self.deepen()?;
+            let right = self.bit_xor()?;
+            expr = Expr::Binary(ExprBinary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+    fn bit_xor(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
+        let mut expr = self.bit_and()?;
+        while self.is_match(&[TokenType::Caret]) {
+            let operator = self.previous().clone();
+            self.deepen()?;
+            let right = self.bit_and()?;
+            expr = Expr::Binary(ExprBinary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        self.depth = depth;
+        Ok(expr)
+    }
+    fn bit_and(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
+        let mut expr = self.comparison()?;
+        while self.is_match(&[TokenType::Ampersand]) {
+            let operator = self.previous().clone();
+            self.deepen()?;
+            let right = self.comparison()?;
+            expr = Expr::Binary(ExprBinary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        self.depth = depth;
         Ok(expr)
     }
 
     fn comparison(&mut self) -> Result<Expr, LoxResult> {
-        let mut expr = self.term()?;
+        let depth = self.depth;
+        let mut expr = self.shift()?;
         while self.is_match(&[
             TokenType::Greater,
             TokenType::GreaterEqual,
@@ -329,7 +566,8 @@ impl<'a> Parser<'a> {
             TokenType::Less,
         ]) {
             let operator = self.previous().clone();
-            let right = self.term()?;
+            self.deepen()?;
+            let right = self.shift()?;
 
             expr = Expr::Binary(ExprBinary {
                 left: Box::new(expr),
@@ -337,12 +575,31 @@ impl<'a> Parser<'a> {
                 right: Box::new(right),
             });
         }
+        self.depth = depth;
+        Ok(expr)
+    }
+    fn shift(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
+        let mut expr = self.term()?;
+        while self.is_match(&[TokenType::LeftShift, TokenType::RightShift]) {
+            let operator = self.previous().clone();
+            self.deepen()?;
+            let right = self.term()?;
+            expr = Expr::Binary(ExprBinary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            })
+        }
+        self.depth = depth;
         Ok(expr)
     }
     fn term(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.factor()?;
         while self.is_match(&[TokenType::Minus, TokenType::Plus]) {
             let operator = self.previous().clone();
+            self.deepen()?;
             let right = self.factor()?;
             expr = Expr::Binary(ExprBinary {
                 left: Box::new(expr),
@@ -350,13 +607,16 @@ impl<'a> Parser<'a> {
                 right: Box::new(right),
             })
         }
+        self.depth = depth;
         Ok(expr)
     }
     fn factor(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.unary()?;
 
         while self.is_match(&[TokenType::Slash, TokenType::Star]) {
             let operator = self.previous().clone();
+            self.deepen()?;
             let right = self.unary()?;
             expr = Expr::Binary(ExprBinary {
                 left: Box::new(expr),
@@ -364,13 +624,14 @@ impl<'a> Parser<'a> {
                 right: Box::new(right),
             })
         }
+        self.depth = depth;
         Ok(expr)
     }
 
     fn unary(&mut self) -> Result<Expr, LoxResult> {
-        if self.is_match(&[TokenType::Bang, TokenType::Minus]) {
+        if self.is_match(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let operator = self.previous().clone();
-            let right = self.unary()?;
+            let right = self.nested(Self::unary)?;
             return Ok(Expr::Unary(ExprUnary {
                 operator,
                 right: Box::new(right),
@@ -380,14 +641,24 @@ impl<'a> Parser<'a> {
     }
 
     fn call(&mut self) -> Result<Expr, LoxResult> {
+        let depth = self.depth;
         let mut expr = self.primary()?;
         loop {
             if self.is_match(&[TokenType::LeftParen]) {
+                self.deepen()?;
                 expr = self.finish_call(&Rc::new(expr))?;
+            } else if self.is_match(&[TokenType::Dot]) {
+                self.deepen()?;
+                let name = self.consume(TokenType::Identifier, "Expect property name after '.'")?;
+                expr = Expr::Get(ExprGet {
+                    object: Box::new(expr),
+                    name,
+                });
             } else {
                 break;
             }
         }
+        self.depth = depth;
         Ok(expr)
     }
 
@@ -395,22 +666,16 @@ impl<'a> Parser<'a> {
         let mut arguments = Vec::new();
 
         if !self.check(TokenType::RightParen) {
-            arguments.push(self.expression()?);
+            arguments.push(self.element()?);
             while self.is_match(&[TokenType::Comma]) {
-                if arguments.len() >= 255 {
-                    if !self.had_error {
-                        let peek = self.peek().clone();
-                        LoxResult::interp_error(
-                            &peek,
-                            "Function call cannot have more than 255 arguments",
-                        );
-                        self.had_error = true
-                    } else {
-                        arguments.push(self.expression()?);
-                    }
+                if arguments.len() >= 255 && self.success() {
+                    let peek = self.peek().clone();
+                    let error =
+                        self.error(&peek, "Function call cannot have more than 255 arguments");
+                    self.errors.push(error);
                 }
+                arguments.push(self.element()?);
             }
-            arguments.push(self.expression()?);
         }
         let paren = self.consume(TokenType::RightParen, "Expected ')' after function call!")?;
 
@@ -421,6 +686,32 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn element(&mut self) -> Result<Expr, LoxResult> {
+        if self.is_match(&[TokenType::Ellipsis]) {
+            let ellipsis = self.previous().clone();
+            let expression = self.expression()?;
+            return Ok(Expr::Spread(ExprSpread {
+                ellipsis,
+                expression: Box::new(expression),
+            }));
+        }
+        self.expression()
+    }
+
+    fn list(&mut self) -> Result<Expr, LoxResult> {
+        let bracket = self.previous().clone();
+        let mut elements = Vec::new();
+        if !self.check(TokenType::RightBracket) {
+            elements.push(self.element()?);
+            while self.is_match(&[TokenType::Comma]) {
+                elements.push(self.element()?);
+            }
+        }
+        self.consume(TokenType::RightBracket, "Expect ']' after list elements")?;
+
+        Ok(Expr::List(ExprList { bracket, elements }))
+    }
+
     fn primary(&mut self) -> Result<Expr, LoxResult> {
         if self.is_match(&[TokenType::String, TokenType::Number, TokenType::Constant]) {
             let tok = self.previous();
@@ -435,6 +726,9 @@ impl<'a> Parser<'a> {
                 name: self.previous().clone(),
             }));
         }
+        if self.is_match(&[TokenType::LeftBracket]) {
+            return self.list();
+        }
         if self.is_match(&[TokenType::LeftParen]) {
             let expr = self.expression()?;
 
@@ -448,6 +742,32 @@ impl<'a> Parser<'a> {
         Err(self.error(&token, "Failed when parsing primary tokens"))
     }
 
+    // Parses one level deeper, or fails once MAX_NESTING levels are open.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, LoxResult>,
+    ) -> Result<T, LoxResult> {
+        self.deepen()?;
+        let result = limits::grow_stack(|| parse(self));
+        self.depth -= 1;
+        result
+    }
+
+    // Opens one more level. The loops parsing left-associative operators
+    // call it for every operator, since each one nests the expression built
+    // so far, and reset the depth when they are done.
+    fn deepen(&mut self) -> Result<(), LoxResult> {
+        if self.depth >= MAX_NESTING {
+            let token = self.peek().clone();
+            return Err(self.error(
+                &token,
+                &format!("Nesting exceeds the limit of {MAX_NESTING} levels"),
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
     fn consume(&mut self, ttype: TokenType, message: &str) -> Result<Token, LoxResult> {
         if self.check(ttype) {
             Ok(self.advance().clone())
@@ -457,8 +777,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn error(&mut self, token: &Token, message: &str) -> LoxResult {
-        self.had_error = true;
+    fn error(&self, token: &Token, message: &str) -> LoxResult {
         LoxResult::parse_error(token, message)
     }
 
@@ -478,6 +797,7 @@ impl<'a> Parser<'a> {
                     | TokenType::If
                     | TokenType::While
                     | TokenType::Print
+                    | TokenType::Assert
                     | TokenType::Return
             ) {
                 return;
@@ -522,3 +842,85 @@ impl<'a> Parser<'a> {
         self.tokens.get(self.current - 1).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Scanner;
+    use rstest::rstest;
+
+    fn parse_expression(source: &str) -> Expr {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        Parser::new(tokens).expression().unwrap()
+    }
+
+    fn parse(source: &str) -> Result<Vec<Stmt>, Vec<LoxResult>> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        Parser::new(tokens).parse()
+    }
+
+    #[rstest]
+    #[case::blocks("if (false) ", "{", "", "}", "")]
+    #[case::groupings("print ", "(", "1", ")", ";")]
+    #[case::unary("print ", "-", "1", "", ";")]
+    #[case::lists("print ", "[", "", "]", ";")]
+    #[case::assignments("var a; ", "a = ", "1", "", ";")]
+    #[case::branches("", "if (true) ", "print 1;", "", "")]
+    #[case::sums("print 1", "", "", " + 1", ";")]
+    #[case::comparisons("print 1", "", "", " < 2", ";")]
+    #[case::calls("print f", "", "", "()", ";")]
+    #[case::properties("print a", "", "", ".b", ";")]
+    fn test_nesting_is_limited(
+        #[case] prefix: &str,
+        #[case] open: &str,
+        #[case] inner: &str,
+        #[case] close: &str,
+        #[case] suffix: &str,
+    ) {
+        let nested = |depth: usize| {
+            format!(
+                "{prefix}{}{inner}{}{suffix}",
+                open.repeat(depth),
+                close.repeat(depth)
+            )
+        };
+        assert!(parse(&nested(100)).is_ok());
+        let Err(errors) = parse(&nested(50000)) else {
+            panic!("expected a parse error");
+        };
+        assert!(matches!(
+            &errors[0],
+            LoxResult::ParseError { message, .. }
+                if message == "Nesting exceeds the limit of 255 levels"
+        ));
+    }
+
+    #[test]
+    fn test_pipeline_prepends_piped_argument() {
+        let Expr::Call(call) = parse_expression("x |> f(y)") else {
+            panic!("expected a call expression");
+        };
+        assert!(matches!(&*call.callee, Expr::Variable(v) if v.name.as_string() == "f"));
+        assert_eq!(call.arguments.len(), 2);
+        assert!(matches!(&call.arguments[0], Expr::Variable(v) if v.name.as_string() == "x"));
+    }
+
+    #[test]
+    fn test_pipeline_chains_left_to_right() {
+        let Expr::Call(outer) = parse_expression("x |> f |> g") else {
+            panic!("expected a call expression");
+        };
+        assert!(matches!(&*outer.callee, Expr::Variable(v) if v.name.as_string() == "g"));
+        assert!(matches!(&outer.arguments[0], Expr::Call(_)));
+    }
+
+    #[test]
+    fn test_pipeline_binds_looser_than_comparison() {
+        let Expr::Binary(binary) = parse_expression("x |> f == y") else {
+            panic!("expected a binary expression");
+        };
+        assert!(matches!(&*binary.left, Expr::Call(_)));
+    }
+}
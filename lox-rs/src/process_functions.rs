@@ -0,0 +1,124 @@
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use std::rc::Rc;
+
+pub fn register(interpreter: &Interpreter) {
+    // Replaced by the CLI with the arguments following the script path.
+    interpreter.define_global("argv", Literal::List(Rc::new(Vec::new())));
+
+    interpreter.register_native(
+        NativeFunction::new("env", |interpreter, args| {
+            permitted(interpreter, args)?;
+            let name = args.string(0)?;
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Err(args.error(&format!("invalid variable name '{name}'")));
+            }
+            Ok(std::env::var(name).map_or(Literal::Nil, Literal::String))
+        })
+        .param("name", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("setEnv", |interpreter, args| {
+            permitted(interpreter, args)?;
+            let name = args.string(0)?;
+            let value = args.string(1)?;
+            if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+                return Err(args.error(&format!("invalid variable '{name}'")));
+            }
+            std::env::set_var(name, value);
+            Ok(Literal::Nil)
+        })
+        .param("name", ParamType::String)
+        .param("value", ParamType::String),
+    );
+
+    interpreter.register_native(NativeFunction::new("cwd", |interpreter, args| {
+        permitted(interpreter, args)?;
+        std::env::current_dir()
+            .map(|dir| Literal::String(dir.to_string_lossy().into_owned()))
+            .map_err(|e| LoxResult::system_error(&format!("{}(): {}", args.name(), e)))
+    }));
+
+    interpreter.register_native(
+        NativeFunction::new("exit", |interpreter, args| {
+            permitted(interpreter, args)?;
+            let code = match args.optional_number(0)? {
+                Some(_) => args.integer(0)?,
+                None => 0,
+            };
+            let Ok(code) = i32::try_from(code) else {
+                return Err(args.error(&format!("{code} is not a valid exit status")));
+            };
+            Err(LoxResult::Exit { code })
+        })
+        .optional("code", ParamType::Number),
+    );
+}
+
+fn permitted(interpreter: &Interpreter, args: &NativeArgs) -> Result<(), LoxResult> {
+    if interpreter.process_access() {
+        Ok(())
+    } else {
+        Err(args.error("process access is disabled by the host"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{call_native, call_native_in};
+    use crate::output::SharedBuffer;
+
+    fn with_access() -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_process_access(true);
+        interpreter
+    }
+
+    #[test]
+    fn test_access_is_denied_by_default() {
+        assert!(matches!(
+            call_native(None, "exit", Vec::<Literal>::new()),
+            Err(LoxResult::RuntimeError { message, .. })
+                if message == "exit(): process access is disabled by the host"
+        ));
+        assert!(call_native(None, "env", ["PATH"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_variable_names() {
+        let interpreter = with_access();
+        assert!(call_native_in(&interpreter, None, "env", ["A=B"]).is_err());
+        assert!(call_native_in(&interpreter, None, "setEnv", ["A=B", "value"]).is_err());
+    }
+
+    #[test]
+    fn test_exit_unwinds_to_the_host() {
+        let mut interpreter = with_access();
+        let output = SharedBuffer::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_diagnostics(Box::new(SharedBuffer::new()));
+        let result = interpreter.run(
+            "
+            fun stop() { while (true) { exit(3); } }
+            print 1;
+            stop();
+            print 2;
+        ",
+        );
+        assert!(matches!(result, Err(LoxResult::Exit { code: 3 })));
+        assert_eq!(output.take(), "1\n");
+    }
+
+    #[test]
+    fn test_argv_defaults_to_empty_list() {
+        let interpreter = Interpreter::new();
+        assert_eq!(
+            interpreter.globals.borrow().get_value("argv"),
+            Some(Literal::List(Rc::new(Vec::new())))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeFunction, ParamType};
+use std::rc::Rc;
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(NativeFunction::new("random", |interpreter, _| {
+        Ok(Literal::Number(interpreter.rng().next_f64()))
+    }));
+
+    interpreter.register_native(
+        NativeFunction::new("randomInt", |interpreter, args| {
+            let low = args.integer(0)?;
+            let high = args.integer(1)?;
+            if low > high {
+                return Err(args.error(&format!("empty range {low} to {high}")));
+            }
+            let span = high.abs_diff(low);
+            let offset = match span.checked_add(1) {
+                Some(bound) => interpreter.rng().below(bound),
+                None => interpreter.rng().next_u64(),
+            };
+            Ok(Literal::Number(low.wrapping_add_unsigned(offset) as f64))
+        })
+        .param("low", ParamType::Number)
+        .param("high", ParamType::Number),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("choice", |interpreter, args| {
+            let values = args.list(0)?;
+            if values.is_empty() {
+                return Err(args.error("cannot choose from an empty list"));
+            }
+            let index = interpreter.rng().below(values.len() as u64) as usize;
+            Ok(values[index].clone())
+        })
+        .param("values", ParamType::List),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("shuffle", |interpreter, args| {
+            let mut values = args.list(0)?.to_vec();
+            let mut rng = interpreter.rng();
+            for i in (1..values.len()).rev() {
+                let j = rng.below(i as u64 + 1) as usize;
+                values.swap(i, j);
+            }
+            Ok(Literal::List(Rc::new(values)))
+        })
+        .param("values", ParamType::List),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("seed", |interpreter, args| {
+            interpreter.seed(args.integer(0)? as u64);
+            Ok(Literal::Nil)
+        })
+        .param("seed", ParamType::Number),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native_in;
+
+    fn numbers(values: &[f64]) -> Literal {
+        Literal::List(Rc::new(
+            values.iter().map(|x| Literal::Number(*x)).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_seed_makes_results_reproducible() {
+        let interpreter = Interpreter::new();
+        let draw = || {
+            call_native_in(&interpreter, None, "seed", vec![Literal::Number(99.0)]).unwrap();
+            let range = vec![Literal::Number(1.0), Literal::Number(6.0)];
+            (
+                call_native_in(&interpreter, None, "random", Vec::<Literal>::new()).unwrap(),
+                call_native_in(&interpreter, None, "randomInt", range).unwrap(),
+                call_native_in(
+                    &interpreter,
+                    None,
+                    "shuffle",
+                    vec![numbers(&[1.0, 2.0, 3.0, 4.0])],
+                )
+                .unwrap(),
+            )
+        };
+        assert_eq!(draw(), draw());
+    }
+
+    #[test]
+    fn test_random_int_is_inclusive() {
+        let interpreter = Interpreter::new();
+        for _ in 0..100 {
+            let range = vec![Literal::Number(-1.0), Literal::Number(1.0)];
+            let Literal::Number(x) =
+                call_native_in(&interpreter, None, "randomInt", range).unwrap()
+            else {
+                panic!("randomInt must return a number");
+            };
+            assert!((-1.0..=1.0).contains(&x));
+        }
+        let empty = vec![Literal::Number(2.0), Literal::Number(1.0)];
+        assert!(call_native_in(&interpreter, None, "randomInt", empty).is_err());
+    }
+
+    #[test]
+    fn test_choice_and_shuffle_keep_elements() {
+        let interpreter = Interpreter::new();
+        let chosen = call_native_in(&interpreter, None, "choice", vec![numbers(&[5.0])]).unwrap();
+        assert_eq!(chosen, Literal::Number(5.0));
+        assert!(call_native_in(&interpreter, None, "choice", vec![numbers(&[])]).is_err());
+
+        let Literal::List(shuffled) = call_native_in(
+            &interpreter,
+            None,
+            "shuffle",
+            vec![numbers(&[3.0, 1.0, 2.0])],
+        )
+        .unwrap() else {
+            panic!("shuffle must return a list");
+        };
+        let mut sorted: Vec<f64> = shuffled
+            .iter()
+            .map(|x| match x {
+                Literal::Number(x) => *x,
+                _ => panic!("unexpected element {x}"),
+            })
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        assert_eq!(sorted, vec![1.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,175 @@
+//! The `regex` namespace, backed by the `regex` crate. Compiled patterns are
+//! cached, so calling these in a loop with the same pattern is cheap.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use regex::{Captures, Regex};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+const CACHE_CAPACITY: usize = 64;
+
+#[derive(Default)]
+struct PatternCache {
+    patterns: RefCell<HashMap<String, Regex>>,
+}
+
+impl PatternCache {
+    /// Compiles the pattern passed as the first argument, reusing an earlier
+    /// compilation when possible.
+    fn compile(&self, args: &NativeArgs) -> Result<Regex, LoxResult> {
+        let pattern = args.string(0)?;
+        if let Some(regex) = self.patterns.borrow().get(&pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(&pattern)
+            .map_err(|e| args.error(&format!("invalid pattern '{pattern}': {e}")))?;
+        let mut patterns = self.patterns.borrow_mut();
+        if patterns.len() >= CACHE_CAPACITY {
+            patterns.clear();
+        }
+        patterns.insert(pattern, regex.clone());
+        Ok(regex)
+    }
+}
+
+pub fn register(interpreter: &Interpreter) {
+    let regex = interpreter.namespace("regex");
+    let cache = Rc::new(PatternCache::default());
+
+    let patterns = Rc::clone(&cache);
+    regex.register_native(
+        NativeFunction::new("match", move |_, args| {
+            let regex = patterns.compile(args)?;
+            Ok(regex
+                .captures(&args.string(1)?)
+                .map_or(Literal::Nil, |captures| groups(&captures)))
+        })
+        .param("pattern", ParamType::String)
+        .param("text", ParamType::String),
+    );
+
+    let patterns = Rc::clone(&cache);
+    regex.register_native(
+        NativeFunction::new("find_all", move |_, args| {
+            let regex = patterns.compile(args)?;
+            let matches = regex
+                .captures_iter(&args.string(1)?)
+                .map(|captures| groups(&captures))
+                .collect();
+            Ok(Literal::List(Rc::new(matches)))
+        })
+        .param("pattern", ParamType::String)
+        .param("text", ParamType::String),
+    );
+
+    let patterns = Rc::clone(&cache);
+    regex.register_native(
+        NativeFunction::new("replace", move |_, args| {
+            let regex = patterns.compile(args)?;
+            let text = args.string(1)?;
+            let replaced = regex.replace_all(&text, args.string(2)?.as_str());
+            Ok(Literal::String(replaced.into_owned()))
+        })
+        .param("pattern", ParamType::String)
+        .param("text", ParamType::String)
+        .param("replacement", ParamType::String),
+    );
+
+    let patterns = cache;
+    regex.register_native(
+        NativeFunction::new("split", move |_, args| {
+            let regex = patterns.compile(args)?;
+            let parts = regex
+                .split(&args.string(1)?)
+                .map(|part| Literal::String(part.to_string()))
+                .collect();
+            Ok(Literal::List(Rc::new(parts)))
+        })
+        .param("pattern", ParamType::String)
+        .param("text", ParamType::String),
+    );
+}
+
+/// The whole match followed by every capture group; groups which did not
+/// participate in the match are `nil`.
+fn groups(captures: &Captures) -> Literal {
+    let groups = captures
+        .iter()
+        .map(|group| group.map_or(Literal::Nil, |m| Literal::String(m.as_str().to_string())))
+        .collect();
+    Literal::List(Rc::new(groups))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callable::LoxCallable;
+    use crate::interpreter::call_native;
+
+    #[test]
+    fn test_match_returns_capture_groups() {
+        let result = call_native(
+            Some("regex"),
+            "match",
+            [r"(\w+)@(\w+)?\.com", "mail: lox@.com"],
+        )
+        .unwrap();
+        assert_eq!(result.to_string(), "[lox@.com, lox, nil]");
+        assert_eq!(
+            call_native(Some("regex"), "match", ["x", "abc"]).unwrap(),
+            Literal::Nil
+        );
+    }
+
+    #[test]
+    fn test_find_all_replace_and_split() {
+        assert_eq!(
+            call_native(Some("regex"), "find_all", [r"\d+", "1 and 22"])
+                .unwrap()
+                .to_string(),
+            "[[1], [22]]"
+        );
+        assert_eq!(
+            call_native(
+                Some("regex"),
+                "replace",
+                [r"(\w+)=(\w+)", "a=b c=d", "$2=$1"]
+            )
+            .unwrap(),
+            Literal::String("b=a d=c".to_string())
+        );
+        assert_eq!(
+            call_native(Some("regex"), "split", [r"\s*,\s*", "a , b,c"])
+                .unwrap()
+                .to_string(),
+            "[a, b, c]"
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_runtime_error() {
+        assert!(matches!(
+            call_native(Some("regex"), "match", ["(", "text"]),
+            Err(LoxResult::RuntimeError { message, .. })
+                if message.starts_with("match(): invalid pattern '('")
+        ));
+    }
+
+    #[test]
+    fn test_compiled_patterns_are_cached() {
+        let cache = PatternCache::default();
+        let interpreter = Interpreter::new();
+        let native = NativeFunction::new("probe", move |_, args| {
+            cache.compile(args)?;
+            cache.compile(args)?;
+            Ok(Literal::Number(cache.patterns.borrow().len() as f64))
+        })
+        .param("pattern", ParamType::String);
+        let result = native.call(&interpreter, vec![Literal::String("a+".to_string())]);
+        assert_eq!(result.unwrap(), Literal::Number(1.0));
+    }
+}
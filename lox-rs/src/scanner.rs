@@ -20,22 +20,24 @@ impl Scanner {
             line: 1,
         }
     }
-    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, LoxResult> {
-        let mut had_error: Option<LoxResult> = None;
+    /// Scans the whole source, returning every error found when it is not
+    /// valid.
+    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, Vec<LoxResult>> {
+        let mut errors = Vec::new();
 
         while !self.is_at_end() {
             self.start = self.current;
             match self.scan_token() {
                 Ok(_) => (),
                 Err(e) => {
-                    had_error = Some(e);
+                    errors.push(e);
                 }
             }
         }
 
         self.tokens.push(Token::eof(self.line));
-        if let Some(e) = had_error {
-            return Err(e);
+        if !errors.is_empty() {
+            return Err(errors);
         }
         Ok(&self.tokens)
     }
@@ -49,12 +51,26 @@ impl Scanner {
             ')' => self.add_token(TokenType::RightParen),
             '{' => self.add_token(TokenType::LeftBrace),
             '}' => self.add_token(TokenType::RightBrace),
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
+            ':' => self.add_token(TokenType::Colon),
+            '.' => {
+                if self.peek() == Some('.') && self.peek_next() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    self.add_token(TokenType::Ellipsis)
+                } else {
+                    self.add_token(TokenType::Dot)
+                }
+            }
             '-' => self.add_token(TokenType::Minus),
             '+' => self.add_token(TokenType::Plus),
             ';' => self.add_token(TokenType::Semicolon),
             '*' => self.add_token(TokenType::Star),
+            '&' => self.add_token(TokenType::Ampersand),
+            '^' => self.add_token(TokenType::Caret),
+            '~' => self.add_token(TokenType::Tilde),
             '!' => {
                 if self.take_expected('=') {
                     self.add_token(TokenType::BangEqual)
@@ -72,6 +88,8 @@ impl Scanner {
             '<' => {
                 if self.take_expected('=') {
                     self.add_token(TokenType::LessEqual)
+                } else if self.take_expected('<') {
+                    self.add_token(TokenType::LeftShift)
                 } else {
                     self.add_token(TokenType::Less)
                 }
@@ -79,10 +97,19 @@ impl Scanner {
             '>' => {
                 if self.take_expected('=') {
                     self.add_token(TokenType::GreaterEqual)
+                } else if self.take_expected('>') {
+                    self.add_token(TokenType::RightShift)
                 } else {
                     self.add_token(TokenType::Greater)
                 }
             }
+            '|' => {
+                if self.take_expected('>') {
+                    self.add_token(TokenType::Pipe)
+                } else {
+                    self.add_token(TokenType::Bar)
+                }
+            }
             '/' => {
                 if self.take_expected('/') {
                     while let Some(ch) = self.peek() {
@@ -152,7 +179,7 @@ impl Scanner {
 
     fn is_alpha_numeric(c: Option<char>) -> bool {
         match c {
-            Some(c) => c.is_ascii_alphanumeric(),
+            Some(c) => c.is_ascii_alphanumeric() || c == '_',
             None => false,
         }
     }
@@ -228,7 +255,26 @@ impl Scanner {
             "var" => Some(TokenType::Var),
             "while" => Some(TokenType::While),
             "break" => Some(TokenType::Break),
+            "assert" => Some(TokenType::Assert),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::inner("find_all", "find_all")]
+    #[case::leading("_private", "_private")]
+    #[case::trailing("x_ ", "x_")]
+    fn test_identifiers_may_contain_underscores(#[case] source: &str, #[case] lexeme: &str) {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert!(tokens[0].is(TokenType::Identifier));
+        assert_eq!(tokens[0].lexeme, lexeme);
+    }
+}
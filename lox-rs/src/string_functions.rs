@@ -0,0 +1,311 @@
+//! String natives. Lengths, positions and indices all count Unicode scalar
+//! values (Rust `char`s), not bytes: `len("żółw")` is 4 and `charAt("żółw", 1)`
+//! is `"ó"`.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use std::rc::Rc;
+
+/// Longest string, in bytes, that `repeat` builds.
+const MAX_REPEAT_BYTES: usize = 1 << 24;
+
+pub fn register(interpreter: &Interpreter) {
+    interpreter.register_native(
+        NativeFunction::new("len", |_, args| match args.get(0) {
+            Literal::String(text) => Ok(Literal::Number(text.chars().count() as f64)),
+            Literal::List(values) => Ok(Literal::Number(values.len() as f64)),
+            other => Err(args.error(&format!("expects a string or a list, received {other}"))),
+        })
+        .param("value", ParamType::Any),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("substr", |_, args| {
+            let chars: Vec<char> = args.string(0)?.chars().collect();
+            let start = position(args, 1, chars.len())?;
+            let end = match args.optional_number(2)? {
+                Some(_) => {
+                    let length = args.integer(2)?;
+                    if length < 0 {
+                        return Err(args.error("length must not be negative"));
+                    }
+                    chars.len().min(start.saturating_add(length as usize))
+                }
+                None => chars.len(),
+            };
+            Ok(Literal::String(chars[start..end].iter().collect()))
+        })
+        .param("text", ParamType::String)
+        .param("start", ParamType::Number)
+        .optional("length", ParamType::Number),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("indexOf", |_, args| {
+            let text = args.string(0)?;
+            let index = text
+                .find(args.string(1)?.as_str())
+                .map_or(-1.0, |byte| text[..byte].chars().count() as f64);
+            Ok(Literal::Number(index))
+        })
+        .param("text", ParamType::String)
+        .param("needle", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("split", |_, args| {
+            let text = args.string(0)?;
+            let separator = args.string(1)?;
+            let parts: Vec<Literal> = if separator.is_empty() {
+                text.chars()
+                    .map(|c| Literal::String(c.to_string()))
+                    .collect()
+            } else {
+                text.split(separator.as_str())
+                    .map(|part| Literal::String(part.to_string()))
+                    .collect()
+            };
+            Ok(Literal::List(Rc::new(parts)))
+        })
+        .param("text", ParamType::String)
+        .param("separator", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("join", |_, args| {
+            let separator = args.optional_string(1)?.unwrap_or_default();
+            let parts: Vec<String> = args.list(0)?.iter().map(|x| x.to_string()).collect();
+            Ok(Literal::String(parts.join(&separator)))
+        })
+        .param("values", ParamType::List)
+        .optional("separator", ParamType::String),
+    );
+
+    transform(interpreter, "trim", |text| text.trim().to_string());
+    transform(interpreter, "upper", str::to_uppercase);
+    transform(interpreter, "lower", str::to_lowercase);
+
+    interpreter.register_native(
+        NativeFunction::new("replace", |_, args| {
+            let text = args.string(0)?;
+            Ok(Literal::String(
+                text.replace(args.string(1)?.as_str(), &args.string(2)?),
+            ))
+        })
+        .param("text", ParamType::String)
+        .param("from", ParamType::String)
+        .param("to", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("startsWith", |_, args| {
+            Ok(Literal::Boolean(
+                args.string(0)?.starts_with(args.string(1)?.as_str()),
+            ))
+        })
+        .param("text", ParamType::String)
+        .param("prefix", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("endsWith", |_, args| {
+            Ok(Literal::Boolean(
+                args.string(0)?.ends_with(args.string(1)?.as_str()),
+            ))
+        })
+        .param("text", ParamType::String)
+        .param("suffix", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("repeat", |_, args| {
+            let count = args.integer(1)?;
+            if count < 0 {
+                return Err(args.error("count must not be negative"));
+            }
+            let text = args.string(0)?;
+            match text.len().checked_mul(count as usize) {
+                Some(bytes) if bytes <= MAX_REPEAT_BYTES => {
+                    Ok(Literal::String(text.repeat(count as usize)))
+                }
+                _ => Err(args.error(&format!(
+                    "result would be longer than {MAX_REPEAT_BYTES} bytes"
+                ))),
+            }
+        })
+        .param("text", ParamType::String)
+        .param("count", ParamType::Number),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("charAt", |_, args| {
+            let text = args.string(0)?;
+            let index = args.integer(1)?;
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| text.chars().nth(index))
+                .map(|c| Literal::String(c.to_string()))
+                .ok_or_else(|| args.error(&format!("index {index} is out of range")))
+        })
+        .param("text", ParamType::String)
+        .param("index", ParamType::Number),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("ord", |_, args| {
+            let text = args.string(0)?;
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(Literal::Number(c as u32 as f64)),
+                _ => Err(args.error("expects a single character")),
+            }
+        })
+        .param("character", ParamType::String),
+    );
+
+    interpreter.register_native(
+        NativeFunction::new("chr", |_, args| {
+            let code = args.integer(0)?;
+            u32::try_from(code)
+                .ok()
+                .and_then(char::from_u32)
+                .map(|c| Literal::String(c.to_string()))
+                .ok_or_else(|| args.error(&format!("{code} is not a valid code point")))
+        })
+        .param("code", ParamType::Number),
+    );
+}
+
+fn transform(interpreter: &Interpreter, name: &str, function: fn(&str) -> String) {
+    interpreter.register_native(
+        NativeFunction::new(name, move |_, args| {
+            Ok(Literal::String(function(&args.string(0)?)))
+        })
+        .param("text", ParamType::String),
+    );
+}
+
+/// Integer argument used as a character position within `0..=length`.
+fn position(args: &NativeArgs, index: usize, length: usize) -> Result<usize, LoxResult> {
+    let value = args.integer(index)?;
+    usize::try_from(value)
+        .ok()
+        .filter(|position| *position <= length)
+        .ok_or_else(|| args.error(&format!("position {value} is out of range")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::call_native;
+
+    #[test]
+    fn test_indexing_counts_unicode_scalar_values() {
+        assert_eq!(
+            call_native(None, "len", vec![Literal::from("żółw")]).unwrap(),
+            Literal::Number(4.0)
+        );
+        assert_eq!(
+            call_native(
+                None,
+                "charAt",
+                vec![Literal::from("żółw"), Literal::Number(1.0)]
+            )
+            .unwrap(),
+            Literal::from("ó")
+        );
+        assert_eq!(
+            call_native(
+                None,
+                "indexOf",
+                vec![Literal::from("żółw"), Literal::from("w")]
+            )
+            .unwrap(),
+            Literal::Number(3.0)
+        );
+        assert_eq!(
+            call_native(
+                None,
+                "substr",
+                vec![
+                    Literal::from("żółw"),
+                    Literal::Number(1.0),
+                    Literal::Number(2.0)
+                ]
+            )
+            .unwrap(),
+            Literal::from("ół")
+        );
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        let parts = call_native(
+            None,
+            "split",
+            vec![Literal::from("a,b,c"), Literal::from(",")],
+        )
+        .unwrap();
+        assert_eq!(parts.to_string(), "[a, b, c]");
+        assert_eq!(
+            call_native(None, "join", vec![parts, Literal::from("-")]).unwrap(),
+            Literal::from("a-b-c")
+        );
+    }
+
+    #[test]
+    fn test_ord_and_chr() {
+        assert_eq!(
+            call_native(None, "ord", vec![Literal::from("é")]).unwrap(),
+            Literal::Number(233.0)
+        );
+        assert_eq!(
+            call_native(None, "chr", vec![Literal::Number(233.0)]).unwrap(),
+            Literal::from("é")
+        );
+        assert!(call_native(None, "ord", vec![Literal::from("ab")]).is_err());
+        assert!(call_native(None, "chr", vec![Literal::Number(-1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_repeat_is_bounded() {
+        assert_eq!(
+            call_native(
+                None,
+                "repeat",
+                vec![Literal::from("ab"), Literal::Number(3.0)]
+            )
+            .unwrap(),
+            Literal::from("ababab")
+        );
+        assert!(matches!(
+            call_native(None, "repeat", vec![Literal::from("ab"), Literal::Number(1e18)]),
+            Err(LoxResult::RuntimeError { message, .. })
+                if message == "repeat(): result would be longer than 16777216 bytes"
+        ));
+    }
+
+    #[test]
+    fn test_out_of_range_positions() {
+        assert!(call_native(
+            None,
+            "charAt",
+            vec![Literal::from("abc"), Literal::Number(3.0)]
+        )
+        .is_err());
+        assert!(call_native(
+            None,
+            "substr",
+            vec![Literal::from("abc"), Literal::Number(4.0)]
+        )
+        .is_err());
+        assert!(call_native(
+            None,
+            "substr",
+            vec![Literal::from("abc"), Literal::Number(0.5)]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! The `time` namespace. Points in time are numbers of milliseconds since the
+//! Unix epoch, the same unit `clock()` uses, and durations are plain numbers
+//! of milliseconds, so they can be added and subtracted with `+` and `-`.
+//! Functions reading calendar fields work in UTC unless `local` is true.
+
+use crate::error::LoxResult;
+use crate::interpreter::*;
+use crate::literal::Literal;
+use crate::namespace::Namespace;
+use crate::native_functions::{NativeArgs, NativeFunction, ParamType};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{
+    DateTime, Datelike, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone,
+    Timelike, Utc,
+};
+use std::time::Instant;
+
+const SECOND: f64 = 1000.0;
+
+pub fn register(interpreter: &Interpreter) {
+    let time = interpreter.namespace("time");
+
+    time.define("SECOND", Literal::Number(SECOND));
+    time.define("MINUTE", Literal::Number(60.0 * SECOND));
+    time.define("HOUR", Literal::Number(3600.0 * SECOND));
+    time.define("DAY", Literal::Number(86400.0 * SECOND));
+
+    time.register_native(NativeFunction::new("now", |_, _| {
+        Ok(Literal::Number(Utc::now().timestamp_millis() as f64))
+    }));
+
+    // Measured from the moment the interpreter was created; unaffected by
+    // changes to the system clock.
+    let start = Instant::now();
+    time.register_native(NativeFunction::new("monotonic", move |_, _| {
+        Ok(Literal::Number(start.elapsed().as_secs_f64() * SECOND))
+    }));
+
+    time.register_native(
+        NativeFunction::new("utcOffset", |_, args| {
+            let timestamp = match args.optional_number(0)? {
+                Some(_) => datetime(args, 0)?,
+                None => Utc::now(),
+            };
+            let offset = Local.offset_from_utc_datetime(&timestamp.naive_utc());
+            Ok(Literal::Number(
+                offset.fix().local_minus_utc() as f64 * SECOND,
+            ))
+        })
+        .optional("timestamp", ParamType::Number),
+    );
+
+    time.register_native(
+        NativeFunction::new("format", |_, args| {
+            let timestamp = datetime(args, 0)?;
+            let items = pattern(args, 1)?;
+            let text = if local(args, 2)? {
+                timestamp
+                    .with_timezone(&Local)
+                    .format_with_items(items.iter())
+            } else {
+                timestamp.fixed_offset().format_with_items(items.iter())
+            };
+            Ok(Literal::String(text.to_string()))
+        })
+        .param("timestamp", ParamType::Number)
+        .param("pattern", ParamType::String)
+        .optional("local", ParamType::Bool),
+    );
+
+    time.register_native(
+        NativeFunction::new("parse", |_, args| {
+            let text = args.string(0)?;
+            let pattern = args.string(1)?;
+            pattern_items(args, &pattern)?;
+            let local = local(args, 2)?;
+            let parsed = match DateTime::parse_from_str(&text, &pattern) {
+                Ok(timestamp) => Some(timestamp.to_utc()),
+                Err(_) => NaiveDateTime::parse_from_str(&text, &pattern)
+                    .or_else(|_| {
+                        NaiveDate::parse_from_str(&text, &pattern)
+                            .map(|date| date.and_time(NaiveTime::MIN))
+                    })
+                    .ok()
+                    .and_then(|naive| {
+                        if local {
+                            Local
+                                .from_local_datetime(&naive)
+                                .earliest()
+                                .map(|t| t.to_utc())
+                        } else {
+                            Some(naive.and_utc())
+                        }
+                    }),
+            };
+            match parsed {
+                Some(timestamp) => Ok(Literal::Number(timestamp.timestamp_millis() as f64)),
+                None => Err(args.error(&format!("'{text}' does not match '{pattern}'"))),
+            }
+        })
+        .param("text", ParamType::String)
+        .param("pattern", ParamType::String)
+        .optional("local", ParamType::Bool),
+    );
+
+    component(&time, "year", |t| t.year() as f64);
+    component(&time, "month", |t| t.month() as f64);
+    component(&time, "day", |t| t.day() as f64);
+    component(&time, "hour", |t| t.hour() as f64);
+    component(&time, "minute", |t| t.minute() as f64);
+    component(&time, "second", |t| t.second() as f64);
+    // 0 is Sunday, as in `%w`.
+    component(&time, "weekday", |t| {
+        t.weekday().num_days_from_sunday() as f64
+    });
+    component(&time, "dayOfYear", |t| t.ordinal() as f64);
+
+    time.register_native(
+        NativeFunction::new("addMonths", |_, args| {
+            let timestamp = datetime(args, 0)?;
+            let months = args.integer(1)?;
+            let count = Months::new(months.unsigned_abs().try_into().unwrap_or(u32::MAX));
+            let shifted = if months < 0 {
+                timestamp.checked_sub_months(count)
+            } else {
+                timestamp.checked_add_months(count)
+            };
+            shifted
+                .map(|t| Literal::Number(t.timestamp_millis() as f64))
+                .ok_or_else(|| args.error("result is out of range"))
+        })
+        .param("timestamp", ParamType::Number)
+        .param("months", ParamType::Number),
+    );
+}
+
+/// Registers a function returning one calendar field of a timestamp.
+fn component(time: &Namespace, name: &str, field: fn(&NaiveDateTime) -> f64) {
+    time.register_native(
+        NativeFunction::new(name, move |_, args| {
+            let timestamp = datetime(args, 0)?;
+            let naive = if local(args, 1)? {
+                timestamp.with_timezone(&Local).naive_local()
+            } else {
+                timestamp.naive_utc()
+            };
+            Ok(Literal::Number(field(&naive)))
+        })
+        .param("timestamp", ParamType::Number)
+        .optional("local", ParamType::Bool),
+    );
+}
+
+fn datetime(args: &NativeArgs, index: usize) -> Result<DateTime<Utc>, LoxResult> {
+    let millis = args.number(index)?;
+    if !millis.is_finite() {
+        return Err(args.error(&format!("{millis} is not a valid timestamp")));
+    }
+    DateTime::from_timestamp_millis(millis.floor() as i64)
+        .ok_or_else(|| args.error(&format!("timestamp {millis} is out of range")))
+}
+
+fn local(args: &NativeArgs, index: usize) -> Result<bool, LoxResult> {
+    match args.get(index) {
+        Literal::Nil => Ok(false),
+        _ => args.boolean(index),
+    }
+}
+
+fn pattern(args: &NativeArgs, index: usize) -> Result<Vec<Item<'static>>, LoxResult> {
+    pattern_items(args, &args.string(index)?)
+}
+
+/// Parses a strftime pattern up front, since chrono would otherwise only
+/// fail while writing the formatted text.
+fn pattern_items(args: &NativeArgs, pattern: &str) -> Result<Vec<Item<'static>>, LoxResult> {
+    let items: Vec<Item<'static>> = StrftimeItems::new(pattern)
+        .map(|item| item.to_owned())
+        .collect();
+    if items.contains(&Item::Error) {
+        return Err(args.error(&format!("invalid pattern '{pattern}'")));
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{call_native, call_native_in};
+
+    // 2024-02-29T13:45:30Z, a Thursday.
+    const LEAP_DAY: f64 = 1709214330000.0;
+
+    #[test]
+    fn test_components_in_utc() {
+        let at = || vec![Literal::Number(LEAP_DAY)];
+        assert_eq!(
+            call_native(Some("time"), "year", at()).unwrap(),
+            Literal::Number(2024.0)
+        );
+        assert_eq!(
+            call_native(Some("time"), "month", at()).unwrap(),
+            Literal::Number(2.0)
+        );
+        assert_eq!(
+            call_native(Some("time"), "day", at()).unwrap(),
+            Literal::Number(29.0)
+        );
+        assert_eq!(
+            call_native(Some("time"), "hour", at()).unwrap(),
+            Literal::Number(13.0)
+        );
+        assert_eq!(
+            call_native(Some("time"), "weekday", at()).unwrap(),
+            Literal::Number(4.0)
+        );
+    }
+
+    #[test]
+    fn test_format_and_parse_roundtrip() {
+        let pattern = "%Y-%m-%d %H:%M:%S";
+        let text = call_native(
+            Some("time"),
+            "format",
+            vec![Literal::Number(LEAP_DAY), Literal::from(pattern)],
+        )
+        .unwrap();
+        assert_eq!(text, Literal::from("2024-02-29 13:45:30"));
+        assert_eq!(
+            call_native(Some("time"), "parse", vec![text, Literal::from(pattern)]).unwrap(),
+            Literal::Number(LEAP_DAY)
+        );
+        assert_eq!(
+            call_native(
+                Some("time"),
+                "parse",
+                vec![
+                    Literal::from("2024-02-29T15:45:30+02:00"),
+                    Literal::from("%Y-%m-%dT%H:%M:%S%:z")
+                ]
+            )
+            .unwrap(),
+            Literal::Number(LEAP_DAY)
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns_and_text() {
+        assert!(call_native(
+            Some("time"),
+            "format",
+            vec![Literal::Number(0.0), Literal::from("%Q")]
+        )
+        .is_err());
+        assert!(call_native(
+            Some("time"),
+            "parse",
+            vec![Literal::from("yesterday"), Literal::from("%Y-%m-%d")]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_add_months_clamps_to_month_end() {
+        let result = call_native(
+            Some("time"),
+            "addMonths",
+            vec![Literal::Number(LEAP_DAY), Literal::Number(12.0)],
+        );
+        let formatted = call_native(
+            Some("time"),
+            "format",
+            vec![result.unwrap(), Literal::from("%Y-%m-%d")],
+        )
+        .unwrap();
+        assert_eq!(formatted, Literal::from("2025-02-28"));
+    }
+
+    #[test]
+    fn test_monotonic_timer_does_not_go_backwards() {
+        let interpreter = Interpreter::new();
+        let monotonic = || {
+            call_native_in(
+                &interpreter,
+                Some("time"),
+                "monotonic",
+                Vec::<Literal>::new(),
+            )
+        };
+        let first = monotonic().unwrap();
+        let second = monotonic().unwrap();
+        let (Literal::Number(first), Literal::Number(second)) = (first, second) else {
+            panic!("time.monotonic must return numbers");
+        };
+        assert!(second >= first);
+    }
+}
@@ -4,13 +4,20 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
+    Colon,
     Dot,
+    Ellipsis,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Ampersand,
+    Caret,
+    Tilde,
 
     // One or two character tokens.
     Bang,
@@ -21,6 +28,10 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    Pipe, // |>
+    Bar,  // |
+    LeftShift,
+    RightShift,
 
     // Literals.
     Identifier,
@@ -43,6 +54,7 @@ pub enum TokenType {
     Var,
     While,
     Break,
+    Assert,
 
     Eof,
 }
@@ -0,0 +1,820 @@
+use crate::error::LoxResult;
+use crate::expr::*;
+use crate::literal::Literal;
+use crate::stmt::*;
+use crate::token::Token;
+use crate::token_type::TokenType;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    Number,
+    String,
+    Function,
+    List,
+}
+
+impl Type {
+    pub fn from_annotation(token: &Token) -> Option<Type> {
+        match token.as_string().as_str() {
+            "Any" => Some(Type::Any),
+            "Nil" => Some(Type::Nil),
+            "Bool" => Some(Type::Bool),
+            "Number" => Some(Type::Number),
+            "String" => Some(Type::String),
+            "Function" => Some(Type::Function),
+            "List" => Some(Type::List),
+            _ => None,
+        }
+    }
+
+    fn of_literal(literal: &Literal) -> Type {
+        match literal {
+            Literal::Nil => Type::Nil,
+            Literal::Boolean(_) => Type::Bool,
+            Literal::Number(_) => Type::Number,
+            Literal::String(_) => Type::String,
+            Literal::Func(_) => Type::Function,
+            Literal::List(_) => Type::List,
+            Literal::Identifier(_) | Literal::Namespace(_) | Literal::UserData(_) => Type::Any,
+        }
+    }
+
+    fn of_annotation(annotation: &Option<Token>) -> Type {
+        annotation
+            .as_ref()
+            .and_then(Type::from_annotation)
+            .unwrap_or(Type::Any)
+    }
+
+    fn accepts(&self, other: &Type) -> bool {
+        *self == Type::Any || *other == Type::Any || self == other
+    }
+
+    fn is_known(&self) -> bool {
+        *self != Type::Any
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Any => "Any",
+            Type::Nil => "Nil",
+            Type::Bool => "Bool",
+            Type::Number => "Number",
+            Type::String => "String",
+            Type::Function => "Function",
+            Type::List => "List",
+        };
+        write!(f, "{name}")
+    }
+}
+
+struct Signature {
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+#[derive(Clone)]
+struct Binding {
+    ty: Type,
+    // Annotated bindings constrain later assignments, inferred ones only
+    // describe the value they currently hold.
+    declared: bool,
+    signature: Option<Rc<Signature>>,
+}
+
+impl Binding {
+    fn dynamic() -> Binding {
+        Binding {
+            ty: Type::Any,
+            declared: false,
+            signature: None,
+        }
+    }
+}
+
+struct Scope {
+    bindings: HashMap<String, Binding>,
+    function_boundary: bool,
+}
+
+impl Scope {
+    fn new(function_boundary: bool) -> Scope {
+        Scope {
+            bindings: HashMap::new(),
+            function_boundary,
+        }
+    }
+}
+
+pub struct TypeChecker {
+    scopes: RefCell<Vec<Scope>>,
+    // Names some function body assigns to. A call may change such a variable
+    // at any point, so it is dynamic unless annotated.
+    assigned_by_functions: RefCell<HashSet<String>>,
+    return_types: RefCell<Vec<Type>>,
+    errors: RefCell<Vec<LoxResult>>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> TypeChecker {
+        TypeChecker {
+            scopes: RefCell::new(vec![Scope::new(false)]),
+            assigned_by_functions: RefCell::new(HashSet::new()),
+            return_types: RefCell::new(Vec::new()),
+            errors: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Walks the statements without executing them, returning every type
+    /// mismatch found.
+    pub fn check(&self, statements: &[Stmt]) -> Result<(), Vec<LoxResult>> {
+        let mut assigned = Vec::new();
+        statements
+            .iter()
+            .for_each(|statement| assigned_by_functions(statement, &mut assigned));
+        *self.assigned_by_functions.borrow_mut() = assigned
+            .iter()
+            .map(|name| name.as_string().to_string())
+            .collect();
+
+        for statement in statements {
+            if let Err(e) = statement.accept(self) {
+                self.errors.borrow_mut().push(e);
+                break;
+            }
+        }
+        let errors = self.errors.take();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn infer(&self, expr: &Expr) -> Result<Type, LoxResult> {
+        expr.accept(self)
+    }
+
+    fn mismatch(&self, token: &Token, message: &str) {
+        self.errors
+            .borrow_mut()
+            .push(LoxResult::type_error(token, message));
+    }
+
+    fn begin_scope(&self, function_boundary: bool) {
+        self.scopes.borrow_mut().push(Scope::new(function_boundary));
+    }
+
+    fn end_scope(&self) {
+        self.scopes.borrow_mut().pop();
+    }
+
+    fn declare(&self, name: &Token, binding: Binding) {
+        if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+            scope.bindings.insert(name.as_string().to_string(), binding);
+        }
+    }
+
+    fn lookup(&self, name: &Token) -> Option<Binding> {
+        let mut crossed_function = false;
+        for scope in self.scopes.borrow().iter().rev() {
+            if let Some(binding) = scope.bindings.get(name.as_string()) {
+                // An enclosing variable without annotation may hold anything
+                // by the time the function body runs.
+                if crossed_function && !binding.declared {
+                    return Some(Binding::dynamic());
+                }
+                return Some(binding.clone());
+            }
+            crossed_function |= scope.function_boundary;
+        }
+        None
+    }
+
+    fn assign(&self, name: &Token, ty: &Type) {
+        let declared = self.lookup(name).filter(|binding| binding.declared);
+        if let Some(binding) = declared {
+            if !binding.ty.accepts(ty) {
+                self.mismatch(
+                    name,
+                    &format!(
+                        "Cannot assign {} to '{}' of type {}",
+                        ty,
+                        name.as_string(),
+                        binding.ty
+                    ),
+                );
+            }
+        }
+        self.widen(name);
+    }
+
+    /// Forgets what is known about the value of `name`: inferred bindings
+    /// become dynamic and annotated ones keep only their declared type.
+    fn widen(&self, name: &Token) {
+        let mut scopes = self.scopes.borrow_mut();
+        let binding = scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.bindings.get_mut(name.as_string()));
+
+        if let Some(binding) = binding {
+            if binding.declared {
+                binding.signature = None;
+            } else {
+                *binding = Binding::dynamic();
+            }
+        }
+    }
+
+    fn assigned_by_function(&self, name: &Token) -> bool {
+        self.assigned_by_functions
+            .borrow()
+            .contains(name.as_string())
+    }
+
+    fn check_block(&self, statements: &[Stmt]) -> Result<(), LoxResult> {
+        for statement in statements {
+            statement.accept(self)?;
+        }
+        Ok(())
+    }
+
+    fn expect_number(&self, operator: &Token, left: &Type, right: &Type) {
+        if !Type::Number.accepts(left) || !Type::Number.accepts(right) {
+            self.unsupported_operands(operator, left, right);
+        }
+    }
+
+    fn expect_list(&self, token: &Token, ty: &Type) {
+        if !Type::List.accepts(ty) {
+            self.mismatch(token, &format!("Expected a List, received {ty}"));
+        }
+    }
+
+    fn unsupported_operands(&self, operator: &Token, left: &Type, right: &Type) {
+        self.mismatch(
+            operator,
+            &format!(
+                "Unsupported operands for '{}': {} and {}",
+                operator.as_string(),
+                left,
+                right
+            ),
+        );
+    }
+}
+
+impl StmtVisitor<()> for TypeChecker {
+    fn visit_block_stmt(&self, stmt: &StmtBlock) -> Result<(), LoxResult> {
+        self.begin_scope(false);
+        let result = self.check_block(&stmt.statements);
+        self.end_scope();
+        result
+    }
+
+    fn visit_expression_stmt(&self, stmt: &StmtExpression) -> Result<(), LoxResult> {
+        self.infer(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_function_stmt(&self, stmt: &StmtFunction) -> Result<(), LoxResult> {
+        let params: Vec<Type> = stmt.param_types.iter().map(Type::of_annotation).collect();
+        let return_type = Type::of_annotation(&stmt.return_type);
+        let annotated =
+            stmt.return_type.is_some() || stmt.param_types.iter().any(|ty| ty.is_some());
+
+        let binding = if !annotated && self.assigned_by_function(&stmt.name) {
+            Binding::dynamic()
+        } else {
+            Binding {
+                ty: Type::Function,
+                declared: annotated,
+                signature: Some(Rc::new(Signature {
+                    params: params.clone(),
+                    return_type: return_type.clone(),
+                })),
+            }
+        };
+        self.declare(&stmt.name, binding);
+
+        self.begin_scope(true);
+        for ((param, ty), annotation) in stmt.params.iter().zip(params).zip(stmt.param_types.iter())
+        {
+            self.declare(
+                param,
+                Binding {
+                    ty,
+                    declared: annotation.is_some(),
+                    signature: None,
+                },
+            );
+        }
+        self.return_types.borrow_mut().push(return_type);
+        let result = self.check_block(&stmt.body);
+        self.return_types.borrow_mut().pop();
+        self.end_scope();
+        result
+    }
+
+    fn visit_destructure_stmt(&self, stmt: &StmtDestructure) -> Result<(), LoxResult> {
+        let source = self.infer(&stmt.initializer)?;
+        self.expect_list(&stmt.bracket, &source);
+        for name in &stmt.names {
+            self.declare(name, Binding::dynamic());
+        }
+        Ok(())
+    }
+
+    fn visit_if_stmt(&self, stmt: &StmtIf) -> Result<(), LoxResult> {
+        self.infer(&stmt.condition)?;
+        stmt.then_branch.accept(self)?;
+        if let Some(else_branch) = &stmt.else_branch {
+            else_branch.accept(self)?;
+        }
+        Ok(())
+    }
+
+    fn visit_print_stmt(&self, stmt: &StmtPrint) -> Result<(), LoxResult> {
+        self.infer(&stmt.expression)?;
+        Ok(())
+    }
+
+    fn visit_return_stmt(&self, stmt: &StmtReturn) -> Result<(), LoxResult> {
+        let actual = if let Some(value) = &stmt.value {
+            self.infer(value)?
+        } else {
+            Type::Nil
+        };
+        let expected = self.return_types.borrow().last().cloned();
+        if let Some(expected) = expected {
+            if !expected.accepts(&actual) {
+                self.mismatch(
+                    &stmt.keyword,
+                    &format!("Function declared to return {expected}, returns {actual}"),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_var_stmt(&self, stmt: &StmtVar) -> Result<(), LoxResult> {
+        let actual = if let Some(initializer) = &stmt.initializer {
+            Some(self.infer(initializer)?)
+        } else {
+            None
+        };
+
+        let binding = if stmt.type_annotation.is_some() {
+            let declared = Type::of_annotation(&stmt.type_annotation);
+            if let Some(actual) = &actual {
+                if !declared.accepts(actual) {
+                    self.mismatch(
+                        &stmt.name,
+                        &format!(
+                            "Cannot initialize '{}' of type {} with {}",
+                            stmt.name.as_string(),
+                            declared,
+                            actual
+                        ),
+                    );
+                }
+            }
+            Binding {
+                ty: declared,
+                declared: true,
+                signature: None,
+            }
+        } else if self.assigned_by_function(&stmt.name) {
+            Binding::dynamic()
+        } else {
+            Binding {
+                ty: actual.unwrap_or(Type::Nil),
+                declared: false,
+                signature: None,
+            }
+        };
+        self.declare(&stmt.name, binding);
+        Ok(())
+    }
+
+    fn visit_while_stmt(&self, stmt: &StmtWhile) -> Result<(), LoxResult> {
+        // Later iterations see the values assigned by earlier ones.
+        let mut assigned = Vec::new();
+        assigned_in_expr(&stmt.condition, &mut assigned);
+        assigned_in_stmt(&stmt.body, &mut assigned);
+        for name in &assigned {
+            self.widen(name);
+        }
+        self.infer(&stmt.condition)?;
+        stmt.body.accept(self)
+    }
+
+    fn visit_assert_stmt(&self, stmt: &StmtAssert) -> Result<(), LoxResult> {
+        self.infer(&stmt.condition)?;
+        if let Some(message) = &stmt.message {
+            self.infer(message)?;
+        }
+        Ok(())
+    }
+
+    fn visit_break_stmt(&self, _stmt: &StmtBreak) -> Result<(), LoxResult> {
+        Ok(())
+    }
+}
+
+impl ExprVisitor<Type> for TypeChecker {
+    fn visit_assign_expr(&self, expr: &ExprAssign) -> Result<Type, LoxResult> {
+        let ty = self.infer(&expr.value)?;
+        self.assign(&expr.name, &ty);
+        Ok(ty)
+    }
+
+    fn visit_binary_expr(&self, expr: &ExprBinary) -> Result<Type, LoxResult> {
+        let left = self.infer(&expr.left)?;
+        let right = self.infer(&expr.right)?;
+
+        match expr.operator.token_type() {
+            TokenType::Minus
+            | TokenType::Slash
+            | TokenType::Star
+            | TokenType::Ampersand
+            | TokenType::Bar
+            | TokenType::Caret
+            | TokenType::LeftShift
+            | TokenType::RightShift => {
+                self.expect_number(&expr.operator, &left, &right);
+                Ok(Type::Number)
+            }
+            TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => {
+                match (&left, &right) {
+                    (Type::Number | Type::Any, Type::Number | Type::Any)
+                    | (Type::String | Type::Any, Type::String | Type::Any) => {}
+                    _ => self.unsupported_operands(&expr.operator, &left, &right),
+                }
+                Ok(Type::Bool)
+            }
+            TokenType::Plus => match (&left, &right) {
+                (Type::Number, Type::Number) => Ok(Type::Number),
+                (Type::String, Type::String | Type::Number) | (Type::Number, Type::String) => {
+                    Ok(Type::String)
+                }
+                (Type::Any, _) | (_, Type::Any) => Ok(Type::Any),
+                _ => {
+                    self.unsupported_operands(&expr.operator, &left, &right);
+                    Ok(Type::Any)
+                }
+            },
+            TokenType::Equals | TokenType::BangEqual => Ok(Type::Bool),
+            _ => Ok(Type::Any),
+        }
+    }
+
+    fn visit_call_expr(&self, expr: &ExprCall) -> Result<Type, LoxResult> {
+        let callee = self.infer(&expr.callee)?;
+        let mut arguments = Vec::new();
+        for argument in &expr.arguments {
+            arguments.push(self.infer(argument)?);
+        }
+
+        if callee.is_known() && callee != Type::Function {
+            self.mismatch(
+                &expr.paren,
+                &format!("Can only call functions, not {callee}"),
+            );
+            return Ok(Type::Any);
+        }
+
+        let Expr::Variable(variable) = &*expr.callee else {
+            return Ok(Type::Any);
+        };
+        if expr
+            .arguments
+            .iter()
+            .any(|argument| matches!(argument, Expr::Spread(_)))
+        {
+            return Ok(Type::Any);
+        }
+        let Some(signature) = self.lookup(&variable.name).and_then(|b| b.signature) else {
+            return Ok(Type::Any);
+        };
+
+        if signature.params.len() != arguments.len() {
+            self.mismatch(
+                &expr.paren,
+                &format!(
+                    "'{}' expects {} arguments, received {}",
+                    variable.name.as_string(),
+                    signature.params.len(),
+                    arguments.len()
+                ),
+            );
+        } else {
+            for (position, (param, argument)) in
+                signature.params.iter().zip(arguments.iter()).enumerate()
+            {
+                if !param.accepts(argument) {
+                    self.mismatch(
+                        &expr.paren,
+                        &format!(
+                            "Argument {} of '{}' expects {}, received {}",
+                            position + 1,
+                            variable.name.as_string(),
+                            param,
+                            argument
+                        ),
+                    );
+                }
+            }
+        }
+        Ok(signature.return_type.clone())
+    }
+
+    fn visit_list_expr(&self, expr: &ExprList) -> Result<Type, LoxResult> {
+        for element in &expr.elements {
+            self.infer(element)?;
+        }
+        Ok(Type::List)
+    }
+
+    fn visit_spread_expr(&self, expr: &ExprSpread) -> Result<Type, LoxResult> {
+        let source = self.infer(&expr.expression)?;
+        self.expect_list(&expr.ellipsis, &source);
+        Ok(Type::Any)
+    }
+
+    fn visit_unpack_expr(&self, expr: &ExprUnpack) -> Result<Type, LoxResult> {
+        let source = self.infer(&expr.value)?;
+        self.expect_list(&expr.bracket, &source);
+        for name in &expr.names {
+            self.assign(name, &Type::Any);
+        }
+        Ok(source)
+    }
+
+    fn visit_get_expr(&self, expr: &ExprGet) -> Result<Type, LoxResult> {
+        let object = self.infer(&expr.object)?;
+        if object.is_known() {
+            self.mismatch(
+                &expr.name,
+                &format!("Only namespaces and host objects have properties, not {object}"),
+            );
+        }
+        Ok(Type::Any)
+    }
+
+    fn visit_grouping_expr(&self, expr: &ExprGrouping) -> Result<Type, LoxResult> {
+        self.infer(&expr.expression)
+    }
+
+    fn visit_literal_expr(&self, expr: &ExprLiteral) -> Result<Type, LoxResult> {
+        Ok(expr.value.as_ref().map_or(Type::Any, Type::of_literal))
+    }
+
+    fn visit_unary_expr(&self, expr: &ExprUnary) -> Result<Type, LoxResult> {
+        let right = self.infer(&expr.right)?;
+        match expr.operator.token_type() {
+            TokenType::Bang => Ok(Type::Bool),
+            TokenType::Minus if right == Type::Number => Ok(Type::Number),
+            TokenType::Tilde => {
+                if !Type::Number.accepts(&right) {
+                    self.mismatch(
+                        &expr.operator,
+                        &format!("Unsupported operand for '~': {right}"),
+                    );
+                }
+                Ok(Type::Number)
+            }
+            _ => Ok(Type::Any),
+        }
+    }
+
+    fn visit_variable_expr(&self, expr: &ExprVariable) -> Result<Type, LoxResult> {
+        Ok(self
+            .lookup(&expr.name)
+            .map_or(Type::Any, |binding| binding.ty))
+    }
+
+    fn visit_logical_expr(&self, expr: &ExprLogical) -> Result<Type, LoxResult> {
+        let left = self.infer(&expr.left)?;
+        let right = self.infer(&expr.right)?;
+        if left == right {
+            Ok(left)
+        } else {
+            Ok(Type::Any)
+        }
+    }
+}
+
+/// Collects the variables `stmt` may assign to, including those assigned
+/// inside nested function bodies.
+// Collects the names assigned in the bodies of the functions `stmt` declares,
+// at any depth.
+fn assigned_by_functions(stmt: &Stmt, names: &mut Vec<Token>) {
+    match stmt {
+        Stmt::Function(function) => function
+            .body
+            .iter()
+            .for_each(|statement| assigned_in_stmt(statement, names)),
+        Stmt::Block(block) => block
+            .statements
+            .iter()
+            .for_each(|statement| assigned_by_functions(statement, names)),
+        Stmt::If(stmt) => {
+            assigned_by_functions(&stmt.then_branch, names);
+            if let Some(else_branch) = &stmt.else_branch {
+                assigned_by_functions(else_branch, names);
+            }
+        }
+        Stmt::While(stmt) => assigned_by_functions(&stmt.body, names),
+        _ => {}
+    }
+}
+
+fn assigned_in_stmt(stmt: &Stmt, names: &mut Vec<Token>) {
+    match stmt {
+        Stmt::Block(block) => block
+            .statements
+            .iter()
+            .for_each(|statement| assigned_in_stmt(statement, names)),
+        Stmt::Expression(stmt) => assigned_in_expr(&stmt.expression, names),
+        Stmt::Print(stmt) => assigned_in_expr(&stmt.expression, names),
+        Stmt::Function(function) => function
+            .body
+            .iter()
+            .for_each(|statement| assigned_in_stmt(statement, names)),
+        Stmt::If(stmt) => {
+            assigned_in_expr(&stmt.condition, names);
+            assigned_in_stmt(&stmt.then_branch, names);
+            if let Some(else_branch) = &stmt.else_branch {
+                assigned_in_stmt(else_branch, names);
+            }
+        }
+        Stmt::Return(stmt) => {
+            if let Some(value) = &stmt.value {
+                assigned_in_expr(value, names);
+            }
+        }
+        Stmt::Var(stmt) => {
+            if let Some(initializer) = &stmt.initializer {
+                assigned_in_expr(initializer, names);
+            }
+        }
+        Stmt::Destructure(stmt) => assigned_in_expr(&stmt.initializer, names),
+        Stmt::While(stmt) => {
+            assigned_in_expr(&stmt.condition, names);
+            assigned_in_stmt(&stmt.body, names);
+        }
+        Stmt::Assert(stmt) => {
+            assigned_in_expr(&stmt.condition, names);
+            if let Some(message) = &stmt.message {
+                assigned_in_expr(message, names);
+            }
+        }
+        Stmt::Break(_) => {}
+    }
+}
+
+fn assigned_in_expr(expr: &Expr, names: &mut Vec<Token>) {
+    match expr {
+        Expr::Assign(assign) => {
+            names.push(assign.name.clone());
+            assigned_in_expr(&assign.value, names);
+        }
+        Expr::Unpack(unpack) => {
+            names.extend(unpack.names.iter().cloned());
+            assigned_in_expr(&unpack.value, names);
+        }
+        Expr::Binary(binary) => {
+            assigned_in_expr(&binary.left, names);
+            assigned_in_expr(&binary.right, names);
+        }
+        Expr::Logical(logical) => {
+            assigned_in_expr(&logical.left, names);
+            assigned_in_expr(&logical.right, names);
+        }
+        Expr::Call(call) => {
+            assigned_in_expr(&call.callee, names);
+            call.arguments
+                .iter()
+                .for_each(|argument| assigned_in_expr(argument, names));
+        }
+        Expr::List(list) => list
+            .elements
+            .iter()
+            .for_each(|element| assigned_in_expr(element, names)),
+        Expr::Get(get) => assigned_in_expr(&get.object, names),
+        Expr::Grouping(grouping) => assigned_in_expr(&grouping.expression, names),
+        Expr::Unary(unary) => assigned_in_expr(&unary.right, names),
+        Expr::Spread(spread) => assigned_in_expr(&spread.expression, names),
+        Expr::Literal(_) | Expr::Variable(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::scanner::Scanner;
+    use rstest::rstest;
+
+    fn check_source(source: &str) -> Result<(), LoxResult> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        TypeChecker::new()
+            .check(&statements)
+            .map_err(|mut errors| errors.remove(0))
+    }
+
+    #[test]
+    fn test_unannotated_code_stays_dynamic() {
+        assert!(check_source("var x = 1; x = \"one\"; print x + \"!\";").is_ok());
+        assert!(check_source("fun f(a) { return a - 1; } print f(\"s\");").is_ok());
+    }
+
+    #[test]
+    fn test_annotated_initializer_mismatch() {
+        assert!(check_source("var x: Number = \"one\";").is_err());
+        assert!(check_source("var x: Number = 1;").is_ok());
+    }
+
+    #[test]
+    fn test_annotated_assignment_mismatch() {
+        assert!(check_source("var x: String; x = true;").is_err());
+    }
+
+    #[test]
+    fn test_inferred_operands_mismatch() {
+        let result = check_source("var a = 1;\nvar b = \"two\";\nprint a - b;");
+        assert!(matches!(
+            result,
+            Err(LoxResult::TypeError { token, .. }) if token.line == 3
+        ));
+    }
+
+    #[test]
+    fn test_function_signature_checked_at_call() {
+        let source = "fun f(a: String): Bool { return a == \"x\"; }";
+        assert!(check_source(&format!("{source} f(1);")).is_err());
+        assert!(check_source(&format!("{source} print f(\"x\") - 1;")).is_err());
+        assert!(check_source(&format!("{source} print !f(\"x\");")).is_ok());
+    }
+
+    #[test]
+    fn test_return_type_mismatch() {
+        assert!(check_source("fun f(): Number { return \"one\"; }").is_err());
+    }
+
+    #[test]
+    fn test_reassigned_function_loses_its_signature() {
+        let source = "fun f(a) { return a; } fun g(a, b) { return a + b; } f = g; print f(1, 2);";
+        assert!(check_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_variables_assigned_in_loop_are_dynamic_in_its_body() {
+        let source = "var x = \"a\"; var i = 0; \
+                      while (i < 2) { if (i == 1) print x - 1; x = 1; i = i + 1; }";
+        assert!(check_source(source).is_ok());
+    }
+
+    #[rstest]
+    #[case::declared_before("var x = \"a\"; fun set() { x = 1; } set(); print x - 1;")]
+    #[case::declared_after(
+        "fun load() { data = \"x\"; } var data = nil; load(); print data + \"!\";"
+    )]
+    #[case::nested_function(
+        "fun outer() { fun inner() { n = \"s\"; } inner(); } var n = 1; outer(); print n + \"!\";"
+    )]
+    #[case::function_reassigned(
+        "fun swap() { f = g; } fun f(a) { return a; } fun g(a, b) { return a; } swap(); f(1, 2);"
+    )]
+    fn test_variables_assigned_by_functions_are_dynamic(#[case] source: &str) {
+        assert!(check_source(source).is_ok());
+    }
+
+    #[test]
+    fn test_enclosing_inferred_variables_are_dynamic_in_functions() {
+        assert!(check_source("var x = \"s\"; fun f() { return x - 1; } x = 1;").is_ok());
+    }
+}
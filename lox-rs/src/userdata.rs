@@ -0,0 +1,203 @@
+use crate::callable::*;
+use crate::literal::*;
+use crate::native_functions::NativeFunction;
+use core::fmt::{Debug, Display};
+use std::any::Any;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type Getter = dyn Fn() -> Literal;
+
+/// An opaque host value (a connection, a request context) handed to scripts.
+/// Scripts can only read the properties and call the methods registered for
+/// it, as `ctx.userId` or `db.query("...")`; hosts get the value back with
+/// [`UserData::value`].
+#[derive(Clone)]
+pub struct UserData {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    type_name: String,
+    value: Rc<dyn Any>,
+    properties: HashMap<String, Box<Getter>>,
+    methods: HashMap<String, Literal>,
+}
+
+impl UserData {
+    /// Starts describing `value` as an object of type `type_name`.
+    pub fn builder<T: 'static>(type_name: &str, value: T) -> UserDataBuilder<T> {
+        UserDataBuilder {
+            type_name: type_name.to_string(),
+            value: Rc::new(value),
+            properties: HashMap::new(),
+            methods: HashMap::new(),
+        }
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.inner.type_name
+    }
+
+    /// The wrapped value, when it is a `T`.
+    pub fn value<T: 'static>(&self) -> Option<Rc<T>> {
+        Rc::clone(&self.inner.value).downcast().ok()
+    }
+
+    /// The current value of a property, or the method with the given name.
+    pub fn get(&self, name: &str) -> Option<Literal> {
+        match self.inner.properties.get(name) {
+            Some(getter) => Some(getter()),
+            None => self.inner.methods.get(name).cloned(),
+        }
+    }
+}
+
+pub struct UserDataBuilder<T> {
+    type_name: String,
+    value: Rc<T>,
+    properties: HashMap<String, Box<Getter>>,
+    methods: HashMap<String, Literal>,
+}
+
+impl<T: 'static> UserDataBuilder<T> {
+    /// Adds a read-only property computed from the value on every access.
+    pub fn property<F>(mut self, name: &str, getter: F) -> Self
+    where
+        F: Fn(&T) -> Literal + 'static,
+    {
+        let value = Rc::clone(&self.value);
+        self.properties
+            .insert(name.to_string(), Box::new(move || getter(&value)));
+        self
+    }
+
+    /// Adds a method. `method` receives the value and builds the native
+    /// function scripts call, named after the method.
+    pub fn method<F>(mut self, method: F) -> Self
+    where
+        F: FnOnce(Rc<T>) -> NativeFunction,
+    {
+        let native = method(Rc::clone(&self.value));
+        self.methods.insert(
+            native.name().to_string(),
+            Literal::Func(Callable {
+                func: Rc::new(native),
+            }),
+        );
+        self
+    }
+
+    pub fn build(self) -> UserData {
+        UserData {
+            inner: Rc::new(Inner {
+                type_name: self.type_name,
+                value: self.value,
+                properties: self.properties,
+                methods: self.methods,
+            }),
+        }
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "userdata {}", self.inner.type_name)
+    }
+}
+
+impl Display for UserData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "userdata {}", self.inner.type_name)
+    }
+}
+
+impl PartialEq for UserData {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl From<UserData> for Literal {
+    fn from(value: UserData) -> Self {
+        Literal::UserData(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::native_functions::ParamType;
+    use crate::Lox;
+    use std::cell::RefCell;
+
+    struct Database {
+        queries: RefCell<Vec<String>>,
+    }
+
+    fn database() -> UserData {
+        let db = Database {
+            queries: RefCell::new(Vec::new()),
+        };
+        UserData::builder("Database", db)
+            .property("count", |db| (db.queries.borrow().len() as i64).into())
+            .method(|db| {
+                NativeFunction::new("query", move |_, args| {
+                    let sql = args.string(0)?;
+                    db.queries.borrow_mut().push(sql.clone());
+                    Ok(vec![sql].into())
+                })
+                .param("sql", ParamType::String)
+            })
+            .build()
+    }
+
+    #[test]
+    fn test_scripts_use_properties_and_methods() {
+        let lox = Lox::new();
+        let db = database();
+        lox.define("db", db.clone().into());
+        assert_eq!(
+            lox.eval("db.query(\"select 1\"); db.count;").unwrap(),
+            Literal::Number(1.0)
+        );
+        let value = db.value::<Database>().unwrap();
+        assert_eq!(*value.queries.borrow(), vec!["select 1".to_string()]);
+        assert!(db.value::<String>().is_none());
+    }
+
+    #[test]
+    fn test_equality_is_identity() {
+        let db = database();
+        assert_eq!(db, db.clone());
+        assert_ne!(db, database());
+    }
+
+    #[test]
+    fn test_scripts_compare_by_identity() {
+        let lox = Lox::new();
+        let db = database();
+        lox.define("db", db.clone().into());
+        lox.define("same", db.into());
+        lox.define("other", database().into());
+        assert_eq!(
+            lox.eval("[db == same, db != same, db == other, db != other];")
+                .unwrap()
+                .to_string(),
+            "[true, false, false, true]"
+        );
+    }
+
+    #[test]
+    fn test_undefined_property() {
+        let mut lox = Lox::new();
+        lox.interpreter_mut()
+            .set_diagnostics(Box::new(crate::SharedBuffer::new()));
+        lox.define("db", database().into());
+        assert!(matches!(
+            lox.eval("db.drop;"),
+            Err(crate::LoxResult::RuntimeError { message, .. })
+                if message == "Undefined property 'drop' on userdata Database"
+        ));
+    }
+}